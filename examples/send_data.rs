@@ -1,7 +1,7 @@
 use africastalking::data::{
     DataUnits, DataValidity, MobileDataRequest, Recipient, RecipientMetadata,
 };
-use africastalking::{AfricasTalkingClient, AfricasTalkingError, Config, Environment, Result};
+use africastalking::{AfricasTalkingClient, Config, Environment, Result};
 use uuid::Uuid;
 
 #[tokio::main]
@@ -12,15 +12,7 @@ async fn main() -> Result<()> {
     // Load .env (ignoring “file not found” errors)
     dotenvy::dotenv().ok();
 
-    // Now you can read env vars:
-    let api_key = std::env::var("AFRICASTALKING_API_KEY").map_err(|_| {
-        AfricasTalkingError::Config("AFRICASTALKING_API_KEY config not set".to_string())
-    })?;
-    let username = std::env::var("AFRICASTALKING_USERNAME").map_err(|_| {
-        AfricasTalkingError::Config("AFRICASTALKING_USERNAME config not set".to_string())
-    })?;
-
-    let config = Config::new(api_key, username.clone()).environment(Environment::Production);
+    let config = Config::from_env()?.environment(Environment::Production);
 
     let client = AfricasTalkingClient::new(config).unwrap();
 
@@ -4,17 +4,35 @@ use crate::{
     config::Config,
     error::{AfricasTalkingError, ApiErrorResponse, Result},
     modules::*,
+    rate_limit::RateLimiter,
+};
+use reqwest::{
+    Client as HttpClient, Method, Proxy, Response,
+    header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderValue},
 };
-use reqwest::{Client as HttpClient, Method, Response, header::HeaderMap};
 use serde::{Serialize, de::DeserializeOwned};
-use std::time::Duration;
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+    time::Duration,
+};
 use tokio::time::sleep;
 
+#[cfg(feature = "media-upload")]
+use reqwest::multipart::Form;
+
 /// Main client for interacting with the AfricasTalking API
+///
+/// Cloning is cheap: the HTTP connection pool and the configuration are
+/// shared, so module accessors like [`sms`](Self::sms) can be called freely.
 #[derive(Debug, Clone)]
 pub struct AfricasTalkingClient {
     pub(crate) http_client: HttpClient,
-    pub(crate) config: Config,
+    pub(crate) config: Arc<Config>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    request_timeout: Option<Duration>,
+    request_headers: HeaderMap,
 }
 
 impl AfricasTalkingClient {
@@ -22,26 +40,103 @@ impl AfricasTalkingClient {
     pub fn new(config: Config) -> Result<Self> {
         config.validate()?;
 
-        let mut headers = HeaderMap::new();
+        // SDK headers are inserted last so they replace configured ones
+        let mut headers = config.default_headers.clone();
         headers.insert("Accept", "application/json".parse().unwrap());
-        headers.insert("apikey", config.api_key.parse().unwrap());
+        let mut api_key: HeaderValue = config.api_key.parse().map_err(|_| {
+            AfricasTalkingError::config("API key contains characters not allowed in a header")
+        })?;
+        // Keeps the key out of the `reqwest::Client` debug output
+        api_key.set_sensitive(true);
+        headers.insert("apikey", api_key);
 
         if let Some(user_agent) = &config.user_agent {
-            headers.insert("User-Agent", user_agent.parse().unwrap());
+            let user_agent = user_agent.parse().map_err(|_| {
+                AfricasTalkingError::config(format!(
+                    "User agent {user_agent:?} contains characters not allowed in a header"
+                ))
+            })?;
+            headers.insert("User-Agent", user_agent);
         }
 
-        let http_client = HttpClient::builder()
+        // Compressed responses are decoded transparently
+        let mut builder = HttpClient::builder()
             .timeout(config.timeout)
             .default_headers(headers)
-            .build()
-            .map_err(AfricasTalkingError::Http)?;
+            .gzip(true)
+            .deflate(true);
+
+        if let Some(proxy) = &config.proxy {
+            let proxy = Proxy::all(proxy).map_err(|e| {
+                AfricasTalkingError::config(format!("Invalid proxy URL {proxy:?}: {e}"))
+            })?;
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(max) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+
+        if let Some(interval) = config.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+
+        let http_client = builder.build().map_err(AfricasTalkingError::Http)?;
+
+        let rate_limiter = config.rate_limit.map(|rps| Arc::new(RateLimiter::new(rps)));
 
         Ok(Self {
             http_client,
-            config,
+            config: Arc::new(config),
+            rate_limiter,
+            request_timeout: None,
+            request_headers: HeaderMap::new(),
         })
     }
 
+    /// Get a client whose requests use a different timeout than `Config::timeout`
+    ///
+    /// The underlying HTTP client is shared, so this is cheap enough to call
+    /// per request:
+    ///
+    /// ```rust,no_run
+    /// # use africastalking::{AfricasTalkingClient, Config};
+    /// # use std::time::Duration;
+    /// # let client = AfricasTalkingClient::new(Config::new("key", "sandbox")).unwrap();
+    /// let sms = client.with_timeout(Duration::from_secs(5)).sms();
+    /// ```
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            request_timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
+
+    /// Get a client that adds `headers` to its requests
+    ///
+    /// These are merged over `Config::default_headers`; the `apikey`,
+    /// `Accept` and `Content-Type` headers are ignored.
+    ///
+    /// ```rust,no_run
+    /// # use africastalking::{AfricasTalkingClient, Config, HeaderMap};
+    /// # let client = AfricasTalkingClient::new(Config::new("key", "sandbox")).unwrap();
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("X-Correlation-Id", "req-42".parse().unwrap());
+    /// let sms = client.with_headers(headers).sms();
+    /// ```
+    pub fn with_headers(&self, mut headers: HeaderMap) -> Self {
+        for name in ["apikey", ACCEPT.as_str(), CONTENT_TYPE.as_str()] {
+            headers.remove(name);
+        }
+
+        let mut request_headers = self.request_headers.clone();
+        request_headers.extend(headers);
+        Self {
+            request_headers,
+            ..self.clone()
+        }
+    }
+
     /// Get the SMS module
     pub fn sms(&self) -> SmsModule {
         SmsModule::new(self.clone())
@@ -62,13 +157,20 @@ impl AfricasTalkingClient {
         ApplicationModule::new(self.clone())
     }
 
-    // Add more modules as they're implemented
-    // pub fn voice(&self) -> VoiceModule { ... }
-    // pub fn payments(&self) -> PaymentsModule { ... }
-    // pub fn data(&self) -> DataModule { ... }
+    /// Get the Voice module
+    pub fn voice(&self) -> VoiceModule {
+        VoiceModule::new(self.clone())
+    }
+
+    /// Get the Payments module
+    pub fn payments(&self) -> PaymentsModule {
+        PaymentsModule::new(self.clone())
+    }
 
     /// Make a POST request with form encoding (default for most endpoints)
-    pub(crate) async fn post<T, R>(&self, endpoint: &str, payload: &T) -> Result<R>
+    ///
+    /// The configured username is added to the form fields.
+    pub(crate) async fn post_form<T, R>(&self, endpoint: &str, payload: &T) -> Result<R>
     where
         T: Serialize,
         R: DeserializeOwned,
@@ -87,6 +189,39 @@ impl AfricasTalkingClient {
             .await
     }
 
+    /// Make a POST request with a multipart form body
+    ///
+    /// A multipart body can only be sent once, so `build_form` is called again
+    /// for every attempt.
+    #[cfg(feature = "media-upload")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "africastalking.request",
+            skip(self, build_form),
+            fields(method = "POST", attempt = tracing::field::Empty)
+        )
+    )]
+    pub(crate) async fn post_multipart<R, F>(&self, endpoint: &str, build_form: F) -> Result<R>
+    where
+        R: DeserializeOwned,
+        F: Fn() -> Result<Form>,
+    {
+        let url = self.get_url(endpoint);
+        self.send_with_retry(|| async {
+            let mut request = self
+                .http_client
+                .post(&url)
+                .headers(self.request_headers.clone())
+                .multipart(build_form()?);
+            if let Some(timeout) = self.request_timeout {
+                request = request.timeout(timeout);
+            }
+            Ok(request.send().await?)
+        })
+        .await
+    }
+
     /// Make a GET request to the API
     pub(crate) async fn get<R>(&self, endpoint: &str) -> Result<R>
     where
@@ -97,6 +232,14 @@ impl AfricasTalkingClient {
     }
 
     /// Make a request with retry logic
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "africastalking.request",
+            skip(self, payload, use_json),
+            fields(attempt = tracing::field::Empty)
+        )
+    )]
     async fn request_with<T, R>(
         &self,
         method: Method,
@@ -107,28 +250,84 @@ impl AfricasTalkingClient {
     where
         T: Serialize,
         R: DeserializeOwned,
+    {
+        self.send_with_retry(|| self.make_request_with(&method, endpoint, payload, use_json))
+            .await
+    }
+
+    /// Send a request built by `send`, retrying connection errors, timeouts,
+    /// 429 and 5xx responses
+    ///
+    /// The attempt number is recorded on the caller's tracing span.
+    async fn send_with_retry<R, F, Fut>(&self, send: F) -> Result<R>
+    where
+        R: DeserializeOwned,
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<Response>>,
     {
         let mut attempts = 0;
         let max_attempts = self.config.max_retries + 1;
 
         loop {
             attempts += 1;
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("attempt", attempts);
 
-            match self
-                .make_request_with(&method, endpoint, payload, use_json)
-                .await
-            {
-                Ok(response) => return self.handle_response(response).await,
-                Err(e) if attempts < max_attempts && e.is_retryable() => {
-                    let delay = Duration::from_millis(1000 * attempts as u64);
-                    sleep(delay).await;
-                    continue;
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+
+            let error = match send().await {
+                // 429 and 5xx responses are worth another attempt
+                Ok(response) => {
+                    let status = response.status();
+                    let retryable = status == 429 || status.is_server_error();
+                    match self.handle_response(response).await {
+                        Err(e) if retryable => e,
+                        result => return result,
+                    }
+                }
+                Err(e) if e.is_retryable() => e,
+                Err(e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(error = %e, "request failed");
+                    return Err(e);
                 }
-                Err(e) => return Err(e),
+            };
+
+            if attempts >= max_attempts {
+                #[cfg(feature = "tracing")]
+                tracing::error!(error = %error, "request failed");
+                return Err(error);
+            }
+
+            if let Some(on_retry) = &self.config.on_retry {
+                on_retry(&error, attempts);
             }
+            let delay = self.retry_delay(attempts);
+            #[cfg(feature = "tracing")]
+            tracing::warn!(error = %error, ?delay, "request failed, retrying");
+            sleep(delay).await;
         }
     }
 
+    /// Exponential backoff delay (`base * 2^(attempt - 1)`, capped at
+    /// `retry_max_delay`) with jitter between half and the full delay
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .config
+            .retry_base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.config.retry_max_delay);
+
+        // RandomState is randomly seeded, which is enough entropy for jitter
+        let random = RandomState::new().build_hasher().finish();
+        let jitter = (random % 1000) as f64 / 1000.0;
+
+        delay.mul_f64(0.5 + jitter * 0.5)
+    }
+
     /// Make a single HTTP request
     async fn make_request_with<T>(
         &self,
@@ -142,40 +341,85 @@ impl AfricasTalkingClient {
     {
         let url = self.get_url(endpoint);
 
-        let mut request = self.http_client.request(method.clone(), &url);
+        let mut request = self
+            .http_client
+            .request(method.clone(), &url)
+            .headers(self.request_headers.clone());
+
+        if let Some(timeout) = self.request_timeout {
+            request = request.timeout(timeout);
+        }
 
         if use_json {
             if let Some(payload) = payload {
                 request = request.json(payload);
             }
         } else {
-            // Add username to all form-encoded requests
-            let mut form_data = vec![("username".to_string(), self.config.username.clone())];
-
-            if let Some(payload) = payload {
-                // Convert payload to form data
-                let payload_str = serde_json::to_string(payload)?;
-                let payload_map: std::collections::HashMap<String, serde_json::Value> =
-                    serde_json::from_str(&payload_str)?;
-
-                for (key, value) in payload_map {
-                    let value_str = match value {
-                        serde_json::Value::String(s) => s,
-                        serde_json::Value::Number(n) => n.to_string(),
-                        serde_json::Value::Bool(b) => b.to_string(),
-                        _ => serde_json::to_string(&value)?,
-                    };
-                    form_data.push((key, value_str));
-                }
-            }
-
+            let form_data = self.construct_form_data(payload)?;
             request = request.form(&form_data);
         }
 
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+
         let response = request.send().await?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            status = response.status().as_u16(),
+            latency_ms = started.elapsed().as_millis() as u64,
+            "received response"
+        );
+
         Ok(response)
     }
 
+    /// Convert a payload into form fields, adding the configured username
+    ///
+    /// Top-level values are encoded as follows:
+    /// - strings, numbers and booleans are sent as-is
+    /// - `null` (unset optional fields) is omitted
+    /// - arrays of scalars are comma-joined, as used for phone number lists
+    /// - arrays of objects and nested objects are sent as a JSON string, which
+    ///   is what the airtime `recipients` field expects
+    fn construct_form_data<T>(&self, payload: Option<&T>) -> Result<Vec<(String, String)>>
+    where
+        T: Serialize,
+    {
+        use serde_json::Value;
+
+        // Add username to all form-encoded requests
+        let mut form_data = vec![("username".to_string(), self.config.username.clone())];
+
+        let Some(payload) = payload else {
+            return Ok(form_data);
+        };
+
+        let Value::Object(fields) = serde_json::to_value(payload)? else {
+            return Err(AfricasTalkingError::Internal(
+                "Form payloads must serialize to an object".to_string(),
+            ));
+        };
+
+        for (key, value) in fields {
+            let value_str = match value {
+                Value::Null => continue,
+                Value::String(s) => s,
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                Value::Array(items) if items.iter().all(is_scalar) => items
+                    .iter()
+                    .map(scalar_to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+                other => serde_json::to_string(&other)?,
+            };
+            form_data.push((key, value_str));
+        }
+
+        Ok(form_data)
+    }
+
     /// Get the full URL for an endpoint path
     fn get_url(&self, path: &str) -> String {
         self.config.build_url(path)
@@ -187,6 +431,12 @@ impl AfricasTalkingClient {
         R: DeserializeOwned,
     {
         let status = response.status();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
         let response_text = response.text().await?;
 
         // Handle rate limiting
@@ -206,17 +456,707 @@ impl AfricasTalkingClient {
                 ));
             }
 
-            return Err(AfricasTalkingError::api_error(
-                format!("HTTP {status}: {response_text}"),
-                status.to_string(),
-                None,
-            ));
+            // Anything else is reduced to a short message; the full body is
+            // kept on the error
+            let summary = if content_type.contains("json") {
+                summarize_json_error_body(&response_text)
+            } else {
+                let is_markup = content_type.contains("xml")
+                    || content_type.contains("html")
+                    || response_text.trim_start().starts_with('<');
+                summarize_error_body(&response_text, is_markup)
+            };
+            let message = if summary.is_empty() {
+                status
+                    .canonical_reason()
+                    .unwrap_or("Unexpected status")
+                    .to_string()
+            } else {
+                summary
+            };
+
+            return Err(AfricasTalkingError::HttpStatus {
+                status: status.as_u16(),
+                message,
+                body: response_text,
+            });
         }
 
         // Parse successful response
-        serde_json::from_str::<R>(&response_text).map_err(|e| {
-            eprintln!("Failed to parse response: {response_text}");
-            AfricasTalkingError::Serialization(e)
+        serde_json::from_str::<R>(&response_text).map_err(|source| {
+            AfricasTalkingError::UnexpectedResponse {
+                body: response_text,
+                source,
+            }
         })
     }
 }
+
+/// Longest error body used as-is in an error message
+const MAX_ERROR_SUMMARY_CHARS: usize = 200;
+
+/// Reduce a non-JSON error body to a single line, dropping markup tags and
+/// truncating long bodies
+fn summarize_error_body(body: &str, is_markup: bool) -> String {
+    let text = if is_markup {
+        let mut text = String::with_capacity(body.len());
+        let mut in_tag = false;
+        for c in body.chars() {
+            match c {
+                '<' => {
+                    in_tag = true;
+                    text.push(' ');
+                }
+                '>' if in_tag => in_tag = false,
+                _ if !in_tag => text.push(c),
+                _ => {}
+            }
+        }
+        text
+    } else {
+        body.to_string()
+    };
+
+    let summary = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if summary.chars().count() > MAX_ERROR_SUMMARY_CHARS {
+        let truncated: String = summary.chars().take(MAX_ERROR_SUMMARY_CHARS).collect();
+        format!("{truncated}...")
+    } else {
+        summary
+    }
+}
+
+/// Pick the message out of a JSON error body that isn't in the AfricasTalking
+/// error format, falling back to the compacted body
+fn summarize_json_error_body(body: &str) -> String {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(body) else {
+        return summarize_error_body(body, false);
+    };
+
+    let message = ["errorMessage", "message", "error", "description"]
+        .iter()
+        .find_map(|key| value.get(*key).and_then(|field| field.as_str()));
+    match message {
+        Some(message) => summarize_error_body(message, false),
+        None => summarize_error_body(&value.to_string(), false),
+    }
+}
+
+fn is_scalar(value: &serde_json::Value) -> bool {
+    matches!(
+        value,
+        serde_json::Value::String(_) | serde_json::Value::Number(_) | serde_json::Value::Bool(_)
+    )
+}
+
+fn scalar_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockAfricasTalking;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use wiremock::{
+        Mock, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    fn user_data() -> serde_json::Value {
+        json!({ "UserData": { "balance": "KES 100.00" } })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn clones_share_the_rate_limit() {
+        let config = Config::new("key", "sandbox").rate_limit(2);
+        let client = AfricasTalkingClient::new(config).unwrap();
+        let clone = client.clone();
+        let limiter = client.rate_limiter.as_ref().unwrap();
+        assert!(Arc::ptr_eq(limiter, clone.rate_limiter.as_ref().unwrap()));
+
+        let start = tokio::time::Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        // The original used up the burst, so the clone has to wait
+        clone.rate_limiter.as_ref().unwrap().acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(500));
+
+        // A separately built client has its own bucket
+        let other = AfricasTalkingClient::new(Config::new("key", "sandbox").rate_limit(2)).unwrap();
+        let start = tokio::time::Instant::now();
+        other.rate_limiter.as_ref().unwrap().acquire().await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_and_calls_on_retry() {
+        let mock = MockAfricasTalking::start().await;
+        Mock::given(method("GET"))
+            .and(path("/version1/user"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .mount(mock.server())
+            .await;
+        mock.application_data(user_data()).await;
+
+        let retries = Arc::new(AtomicU32::new(0));
+        let counter = retries.clone();
+        let config = mock
+            .config()
+            .max_retries(3)
+            .retry_base_delay(Duration::from_millis(1))
+            .on_retry(Arc::new(move |error, attempt| {
+                assert!(error.is_server_error());
+                assert_eq!(counter.fetch_add(1, Ordering::SeqCst) + 1, attempt);
+            }));
+        let client = AfricasTalkingClient::new(config).unwrap();
+
+        let response = client.application().get_data().await.unwrap();
+        assert_eq!(response.user_data.balance, "KES 100.00");
+        assert_eq!(retries.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limited_requests_until_attempts_run_out() {
+        let mock = MockAfricasTalking::start().await;
+        mock.mock_json("GET", "/version1/user", 429, json!({}))
+            .await;
+
+        let retries = Arc::new(AtomicU32::new(0));
+        let counter = retries.clone();
+        let config = mock
+            .config()
+            .max_retries(2)
+            .retry_base_delay(Duration::from_millis(1))
+            .on_retry(Arc::new(move |_, _| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        let client = AfricasTalkingClient::new(config).unwrap();
+
+        let error = client.application().get_data().await.unwrap_err();
+        assert!(matches!(error, AfricasTalkingError::RateLimit { .. }));
+        assert_eq!(retries.load(Ordering::SeqCst), 2);
+        assert_eq!(mock.server().received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        let mock = MockAfricasTalking::start().await;
+        mock.mock_json(
+            "GET",
+            "/version1/user",
+            400,
+            json!({ "ErrorMessage": "Invalid request" }),
+        )
+        .await;
+
+        let config = mock
+            .config()
+            .max_retries(3)
+            .retry_base_delay(Duration::from_millis(1))
+            .on_retry(Arc::new(|_, _| panic!("client errors must not be retried")));
+        let client = AfricasTalkingClient::new(config).unwrap();
+
+        assert!(client.application().get_data().await.is_err());
+        assert_eq!(mock.server().received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn keeps_api_error_fields() {
+        let mock = MockAfricasTalking::start().await;
+        mock.mock_json(
+            "GET",
+            "/version1/user",
+            400,
+            json!({
+                "ErrorMessage": "Invalid username",
+                "ErrorCode": "InvalidUsername",
+                "MoreInfo": "{\"field\": \"username\"}"
+            }),
+        )
+        .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let error = client.application().get_data().await.unwrap_err();
+        match &error {
+            AfricasTalkingError::Api { message, code, .. } => {
+                assert_eq!(message, "Invalid username");
+                assert_eq!(code, "InvalidUsername");
+            }
+            other => panic!("expected an API error, got {other:?}"),
+        }
+        assert_eq!(error.more_info_json().unwrap()["field"], "username");
+    }
+
+    #[tokio::test]
+    async fn summarizes_unrecognised_json_error_bodies() {
+        let mock = MockAfricasTalking::start().await;
+        let body = json!({ "message": "Upstream unavailable", "trace": "x".repeat(500) });
+        mock.mock_json("GET", "/version1/user", 404, body.clone())
+            .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let error = client.application().get_data().await.unwrap_err();
+        assert_eq!(error.status_code(), Some(404));
+        assert!(error.more_info_json().is_none());
+        match error {
+            AfricasTalkingError::HttpStatus {
+                status,
+                message,
+                body: raw,
+            } => {
+                assert_eq!(status, 404);
+                assert_eq!(message, "Upstream unavailable");
+                assert_eq!(
+                    serde_json::from_str::<serde_json::Value>(&raw).unwrap(),
+                    body
+                );
+            }
+            other => panic!("expected an HTTP status error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn summarizes_markup_and_text_error_bodies() {
+        let mock = MockAfricasTalking::start().await;
+        let xml =
+            "<?xml version=\"1.0\"?>\n<Error>\n  <Message>Gateway   timeout</Message>\n</Error>";
+        Mock::given(method("GET"))
+            .and(path("/version1/user"))
+            .respond_with(ResponseTemplate::new(400).set_body_raw(xml, "application/xml"))
+            .mount(mock.server())
+            .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        match client.application().get_data().await.unwrap_err() {
+            AfricasTalkingError::HttpStatus {
+                status,
+                message,
+                body,
+            } => {
+                assert_eq!(status, 400);
+                assert_eq!(message, "Gateway timeout");
+                assert_eq!(body, xml);
+            }
+            other => panic!("expected an HTTP status error, got {other:?}"),
+        }
+
+        let mock = MockAfricasTalking::start().await;
+        let text = "a".repeat(MAX_ERROR_SUMMARY_CHARS + 50);
+        Mock::given(method("GET"))
+            .and(path("/version1/user"))
+            .respond_with(ResponseTemplate::new(403).set_body_raw(text.clone(), "text/plain"))
+            .mount(mock.server())
+            .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        match client.application().get_data().await.unwrap_err() {
+            AfricasTalkingError::HttpStatus { message, body, .. } => {
+                assert_eq!(message.len(), MAX_ERROR_SUMMARY_CHARS + 3);
+                assert!(message.ends_with("..."));
+                assert_eq!(body, text);
+            }
+            other => panic!("expected an HTTP status error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn with_timeout_overrides_the_configured_timeout() {
+        let mock = MockAfricasTalking::start().await;
+        Mock::given(method("GET"))
+            .and(path("/version1/user"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(user_data())
+                    .set_delay(Duration::from_millis(300)),
+            )
+            .mount(mock.server())
+            .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let error = client
+            .with_timeout(Duration::from_millis(50))
+            .application()
+            .get_data()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AfricasTalkingError::Timeout), "{error:?}");
+
+        // The original client keeps the configured timeout
+        assert!(client.application().get_data().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn decodes_gzip_responses() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(user_data().to_string().as_bytes())
+            .unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mock = MockAfricasTalking::start().await;
+        Mock::given(method("GET"))
+            .and(path("/version1/user"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-Encoding", "gzip")
+                    .set_body_raw(compressed, "application/json"),
+            )
+            .mount(mock.server())
+            .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let response = client.application().get_data().await.unwrap();
+        assert_eq!(response.user_data.balance, "KES 100.00");
+
+        let requests = mock.server().received_requests().await.unwrap();
+        let accept_encoding = requests[0].headers["accept-encoding"].to_str().unwrap();
+        assert!(accept_encoding.contains("gzip"), "{accept_encoding}");
+    }
+
+    #[tokio::test]
+    async fn custom_headers_reach_the_wire_without_replacing_sdk_headers() {
+        let mock = MockAfricasTalking::start().await;
+        mock.make_call(json!({ "entries": [], "errorMessage": "None" }))
+            .await;
+
+        let mut defaults = HeaderMap::new();
+        defaults.insert("apikey", "default-key".parse().unwrap());
+        defaults.insert("X-Tenant", "acme".parse().unwrap());
+        let client = AfricasTalkingClient::new(mock.config().default_headers(defaults)).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Correlation-Id", "req-42".parse().unwrap());
+        headers.insert("apikey", "per-request-key".parse().unwrap());
+        headers.insert(CONTENT_TYPE, "text/plain".parse().unwrap());
+        client
+            .with_headers(headers)
+            .voice()
+            .make_call(crate::voice::MakeCallRequest::new(
+                "+254711082000",
+                vec!["+254711082001"],
+            ))
+            .await
+            .unwrap();
+
+        let requests = mock.server().received_requests().await.unwrap();
+        let headers = &requests[0].headers;
+        assert_eq!(headers["x-correlation-id"], "req-42");
+        assert_eq!(headers["x-tenant"], "acme");
+        assert_eq!(headers["apikey"], "test-api-key");
+        assert_eq!(headers["content-type"], "application/json");
+        assert_eq!(headers.get_all("apikey").iter().count(), 1);
+        assert_eq!(headers.get_all("content-type").iter().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn post_form_sends_a_url_encoded_body() {
+        let mock = MockAfricasTalking::start().await;
+        mock.send_sms(json!({ "SMSMessageData": { "Message": "Sent", "Recipients": [] } }))
+            .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        client
+            .sms()
+            .send(crate::sms::SendSmsRequest::new(
+                vec!["+254711082000"],
+                "Hello & welcome",
+            ))
+            .await
+            .unwrap();
+
+        let requests = mock.server().received_requests().await.unwrap();
+        assert_eq!(
+            requests[0].headers["content-type"],
+            "application/x-www-form-urlencoded"
+        );
+        let fields: Vec<(String, String)> =
+            serde_urlencoded::from_bytes(&requests[0].body).unwrap();
+        assert!(fields.contains(&("username".into(), "sandbox".into())));
+        assert!(fields.contains(&("to".into(), "+254711082000".into())));
+        assert!(fields.contains(&("message".into(), "Hello & welcome".into())));
+    }
+
+    #[tokio::test]
+    async fn malformed_success_bodies_keep_the_raw_body() {
+        let mock = MockAfricasTalking::start().await;
+        let body = json!({ "UserData": { "credit": "KES 100.00" } });
+        mock.application_data(body.clone()).await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        match client.application().get_data().await.unwrap_err() {
+            AfricasTalkingError::UnexpectedResponse { body: raw, source } => {
+                assert_eq!(
+                    serde_json::from_str::<serde_json::Value>(&raw).unwrap(),
+                    body
+                );
+                assert!(source.to_string().contains("balance"), "{source}");
+            }
+            other => panic!("expected an unexpected response error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn form_data_json_encodes_airtime_recipients() {
+        let client = AfricasTalkingClient::new(Config::new("key", "sandbox")).unwrap();
+        let request = crate::airtime::SendAirtimeRequest::new(vec![
+            crate::airtime::AirtimeRecipient::new("+254711082000", "100", crate::Currency::Kes),
+            crate::airtime::AirtimeRecipient::new("+254711082001", "50", crate::Currency::Kes),
+        ]);
+
+        let fields = client.construct_form_data(Some(&request)).unwrap();
+        assert_eq!(fields[0], ("username".into(), "sandbox".into()));
+        let (_, recipients) = fields.iter().find(|(key, _)| key == "recipients").unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(recipients).unwrap(),
+            json!([
+                { "phoneNumber": "+254711082000", "currencyCode": "KES", "amount": "100" },
+                { "phoneNumber": "+254711082001", "currencyCode": "KES", "amount": "50" }
+            ])
+        );
+        assert!(!fields.iter().any(|(key, _)| key == "maxNumRetry"));
+    }
+
+    #[test]
+    fn form_data_comma_joins_sms_recipients() {
+        let client = AfricasTalkingClient::new(Config::new("key", "sandbox")).unwrap();
+        let request =
+            crate::sms::SendSmsRequest::new(vec!["+254711082000", "+254711082001"], "Hello");
+
+        let fields = client.construct_form_data(Some(&request)).unwrap();
+        assert_eq!(
+            fields,
+            [
+                ("username".to_string(), "sandbox".to_string()),
+                ("message".to_string(), "Hello".to_string()),
+                ("to".to_string(), "+254711082000,+254711082001".to_string()),
+            ]
+        );
+
+        // Scalar lists are joined the same way
+        #[derive(Serialize)]
+        struct Numbers {
+            numbers: Vec<&'static str>,
+        }
+        let fields = client
+            .construct_form_data(Some(&Numbers {
+                numbers: vec!["+254711082000", "+254711082001"],
+            }))
+            .unwrap();
+        assert_eq!(fields[1].1, "+254711082000,+254711082001");
+    }
+
+    #[test]
+    fn retry_delay_grows_and_is_capped() {
+        let config = Config::new("key", "sandbox")
+            .retry_base_delay(Duration::from_millis(100))
+            .retry_max_delay(Duration::from_secs(1));
+        let client = AfricasTalkingClient::new(config).unwrap();
+
+        for (attempt, full) in [
+            (1, 100),
+            (2, 200),
+            (3, 400),
+            (4, 800),
+            (5, 1000),
+            (40, 1000),
+        ] {
+            let full = Duration::from_millis(full);
+            for _ in 0..20 {
+                let delay = client.retry_delay(attempt);
+                assert!(
+                    delay >= full / 2 && delay <= full,
+                    "attempt {attempt}: {delay:?} outside {:?}..={full:?}",
+                    full / 2
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn emits_request_spans_without_the_api_key() {
+        use std::sync::Mutex;
+        use tracing::{
+            Event, Subscriber,
+            field::{Field, Visit},
+            span::{Attributes, Id, Record},
+        };
+        use tracing_subscriber::{Layer, layer::Context, prelude::*};
+
+        /// Every span and event field seen, as `(span or event name, field, value)`
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<(String, String, String)>>>);
+
+        struct FieldVisitor<'a>(&'a Recorder, &'a str);
+
+        impl Visit for FieldVisitor<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0.0.lock().unwrap().push((
+                    self.1.to_string(),
+                    field.name().to_string(),
+                    format!("{value:?}"),
+                ));
+            }
+        }
+
+        impl<S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>> Layer<S> for Recorder {
+            fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+                attrs.record(&mut FieldVisitor(self, attrs.metadata().name()));
+            }
+
+            fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+                let name = ctx.span(id).unwrap().name();
+                values.record(&mut FieldVisitor(self, name));
+            }
+
+            fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+                event.record(&mut FieldVisitor(self, "event"));
+            }
+        }
+
+        let recorder = Recorder::default();
+        let _guard = tracing_subscriber::registry()
+            .with(recorder.clone())
+            .set_default();
+
+        let mock = MockAfricasTalking::start().await;
+        Mock::given(method("GET"))
+            .and(path("/version1/user"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(mock.server())
+            .await;
+        mock.application_data(user_data()).await;
+        let config = mock
+            .config()
+            .max_retries(1)
+            .retry_base_delay(Duration::from_millis(1));
+        let client = AfricasTalkingClient::new(config).unwrap();
+        client.application().get_data().await.unwrap();
+
+        let fields = recorder.0.lock().unwrap();
+        let span_field = |field: &str| {
+            fields
+                .iter()
+                .filter(|(name, key, _)| name == "africastalking.request" && key == field)
+                .map(|(_, _, value)| value.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(span_field("method"), ["GET"]);
+        assert_eq!(span_field("endpoint"), ["\"/version1/user\""]);
+        assert_eq!(span_field("attempt"), ["1", "2"]);
+        assert!(
+            fields
+                .iter()
+                .any(|(name, key, value)| name == "event" && key == "status" && value == "200")
+        );
+
+        for (name, key, value) in fields.iter() {
+            assert_ne!(key, "apikey", "{name} has an apikey field");
+            assert!(
+                !value.contains("test-api-key"),
+                "{name}.{key} leaks the api key"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_proxy_urls_are_config_errors() {
+        let config = Config::new("key", "sandbox").proxy("http://[not-a-host");
+        match AfricasTalkingClient::new(config).unwrap_err() {
+            AfricasTalkingError::Config(message) => {
+                assert!(message.contains("http://[not-a-host"), "{message}")
+            }
+            other => panic!("expected a config error, got {other:?}"),
+        }
+
+        let config = Config::new("key", "sandbox").proxy("http://proxy.internal:8080");
+        assert!(AfricasTalkingClient::new(config).is_ok());
+    }
+
+    #[test]
+    fn invalid_header_values_are_config_errors() {
+        let config = Config::new("atsk_123\nX-Injected: 1", "sandbox");
+        match AfricasTalkingClient::new(config).unwrap_err() {
+            AfricasTalkingError::Config(message) => {
+                assert!(!message.contains("atsk_123"), "{message}")
+            }
+            other => panic!("expected a config error, got {other:?}"),
+        }
+
+        let config = Config::new("key", "sandbox").user_agent("my-app\r\n");
+        assert!(matches!(
+            AfricasTalkingClient::new(config),
+            Err(AfricasTalkingError::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn connection_pool_settings_build_a_working_client() {
+        let mock = MockAfricasTalking::start().await;
+        mock.application_data(user_data()).await;
+        let config = mock
+            .config()
+            .pool_max_idle_per_host(4)
+            .tcp_keepalive(Duration::from_secs(30));
+        let client = AfricasTalkingClient::new(config).unwrap();
+
+        let response = client.application().get_data().await.unwrap();
+        assert_eq!(response.user_data.balance, "KES 100.00");
+    }
+
+    #[tokio::test]
+    async fn module_accessors_share_the_config_of_clones() {
+        use crate::{
+            airtime::{AirtimeRecipient, SendAirtimeRequest},
+            sms::SendSmsRequest,
+            voice::MakeCallRequest,
+        };
+
+        let mock = MockAfricasTalking::start().await;
+        mock.send_sms(json!({ "SMSMessageData": { "Message": "Sent", "Recipients": [] } }))
+            .await;
+        mock.make_call(json!({ "entries": [], "errorMessage": "None" }))
+            .await;
+        mock.send_airtime(json!({
+            "errorMessage": "None",
+            "numSent": 1,
+            "totalAmount": "KES 10.0000",
+            "totalDiscount": "KES 0.4000",
+            "responses": []
+        }))
+        .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+        let clone = client.clone();
+        assert!(Arc::ptr_eq(&client.config, &clone.config));
+
+        clone
+            .sms()
+            .send(SendSmsRequest::new(vec!["+254711082000"], "Hello"))
+            .await
+            .unwrap();
+        clone
+            .voice()
+            .make_call(MakeCallRequest::new("+254711082000", vec!["+254711082001"]))
+            .await
+            .unwrap();
+        let recipient = AirtimeRecipient::kes("+254711082000", 10.0).unwrap();
+        clone
+            .airtime()
+            .send(SendAirtimeRequest::new(vec![recipient]))
+            .await
+            .unwrap();
+
+        let requests = mock.server().received_requests().await.unwrap();
+        assert_eq!(requests.len(), 3);
+    }
+}
@@ -1,8 +1,9 @@
 //! Configuration management for the AfricasTalking SDK
 
 use crate::error::{AfricasTalkingError, Result};
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::{fmt, sync::Arc, time::Duration};
 
 /// Environment configuration
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -11,22 +12,52 @@ pub enum Environment {
     Sandbox,
     /// Production environment
     Production,
+    /// Self-hosted gateway or regional deployment
+    ///
+    /// Standard API requests go to `base_url`, which may carry its own scheme
+    /// and port. The other services (`voice.`, `bundles.`, ...) are reached by
+    /// prefixing their subdomain to `base_domain`, just like the hosted
+    /// environments.
+    ///
+    /// ```rust
+    /// use africastalking::{Config, Environment};
+    ///
+    /// let config = Config::new("key", "app").environment(Environment::Custom {
+    ///     base_url: "http://localhost:8080".into(),
+    ///     base_domain: "at.example.com".into(),
+    /// });
+    /// assert_eq!(
+    ///     config.build_url("/version1/messaging"),
+    ///     "http://localhost:8080/version1/messaging"
+    /// );
+    /// assert_eq!(config.build_url("/call"), "https://voice.at.example.com/call");
+    /// assert_eq!(
+    ///     config.build_url("/mobile/data/request"),
+    ///     "https://bundles.at.example.com/mobile/data/request"
+    /// );
+    /// ```
+    Custom {
+        base_url: String,
+        base_domain: String,
+    },
 }
 
 impl Environment {
     /// Get the base URL for the environment
-    pub fn base_url(&self) -> &'static str {
+    pub fn base_url(&self) -> &str {
         match self {
             Environment::Sandbox => "https://api.sandbox.africastalking.com",
             Environment::Production => "https://api.africastalking.com",
+            Environment::Custom { base_url, .. } => base_url,
         }
     }
 
     /// Get the base domain
-    fn base_domain(&self) -> &'static str {
+    fn base_domain(&self) -> &str {
         match self {
             Environment::Sandbox => "sandbox.africastalking.com",
             Environment::Production => "africastalking.com",
+            Environment::Custom { base_domain, .. } => base_domain,
         }
     }
 }
@@ -52,7 +83,7 @@ impl Endpoint {
         let domain = environment.base_domain();
         match self {
             Endpoint::Standard => {
-                format!("https://api.{}{}", domain, path)
+                format!("{}{}", environment.base_url().trim_end_matches('/'), path)
             }
             Endpoint::MobileData => {
                 format!("https://bundles.{}{}", domain, path)
@@ -67,7 +98,9 @@ impl Endpoint {
                 // Content uses version1 path in sandbox, but is a separate domain in production
                 match environment {
                     Environment::Sandbox => format!("https://api.{}/version1{}", domain, path),
-                    Environment::Production => format!("https://content.{}/version1{}", domain, path),
+                    Environment::Production | Environment::Custom { .. } => {
+                        format!("https://content.{}/version1{}", domain, path)
+                    }
                 }
             }
         }
@@ -83,7 +116,11 @@ impl EndpointMap {
     fn get(&self, path: &str) -> Endpoint {
         if path.contains("mobile/data") || path.contains("query/transaction") || path.contains("query/wallet") {
             Endpoint::MobileData
-        } else if path.contains("voice") {
+        } else if path.contains("voice")
+            || path.starts_with("/call")
+            || path.starts_with("/queueStatus")
+            || path.starts_with("/mediaUpload")
+        {
             Endpoint::Voice
         } else if path.contains("insights") {
             Endpoint::Insights
@@ -95,8 +132,26 @@ impl EndpointMap {
     }
 }
 
+/// Callback run before each retry with the error and the attempt that failed
+pub type RetryHook = Arc<dyn Fn(&AfricasTalkingError, u32) + Send + Sync>;
+
+/// Longest request timeout `Config::validate` accepts
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Most retries `Config::validate` accepts
+pub const MAX_RETRIES: u32 = 10;
+
 /// Configuration for the AfricasTalking client
-#[derive(Debug, Clone)]
+///
+/// The `Debug` output redacts the API key:
+///
+/// ```rust
+/// use africastalking::Config;
+///
+/// let config = Config::new("atsk_secret", "sandbox");
+/// assert!(!format!("{config:?}").contains("atsk_secret"));
+/// ```
+#[derive(Clone)]
 pub struct Config {
     /// API key for authentication
     pub api_key: String,
@@ -104,16 +159,62 @@ pub struct Config {
     pub username: String,
     /// Environment (sandbox or production)
     pub environment: Environment,
-    /// Request timeout duration
+    /// Request timeout duration, at most [`MAX_TIMEOUT`]
     pub timeout: Duration,
-    /// Maximum number of retry attempts
+    /// Maximum number of retry attempts, at most [`MAX_RETRIES`]
     pub max_retries: u32,
+    /// Base delay for exponential retry backoff
+    pub retry_base_delay: Duration,
+    /// Upper bound for a single retry delay
+    pub retry_max_delay: Duration,
+    /// Maximum requests per second sent by the client and its clones
+    pub rate_limit: Option<u32>,
     /// Custom user agent string
     pub user_agent: Option<String>,
+    /// Base URL that replaces the AfricasTalking hosts for every endpoint
+    pub base_url_override: Option<String>,
+    /// Registered short code or alphanumeric sender ID used for bulk SMS
+    pub sms_short_code: Option<String>,
+    /// Proxy URL all requests are routed through
+    pub proxy: Option<String>,
+    /// Idle connections kept open per host; unlimited by default
+    pub pool_max_idle_per_host: Option<usize>,
+    /// TCP keep-alive interval; disabled by default
+    pub tcp_keepalive: Option<Duration>,
+    /// Extra headers sent with every request, e.g. a correlation ID
+    pub default_headers: HeaderMap,
+    /// Called before each retry, e.g. to record metrics
+    pub on_retry: Option<RetryHook>,
     /// Map of endpoint paths to their endpoint types
     endpoint_map: EndpointMap,
 }
 
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("api_key", &"***redacted***")
+            .field("username", &self.username)
+            .field("environment", &self.environment)
+            .field("timeout", &self.timeout)
+            .field("max_retries", &self.max_retries)
+            .field("retry_base_delay", &self.retry_base_delay)
+            .field("retry_max_delay", &self.retry_max_delay)
+            .field("rate_limit", &self.rate_limit)
+            .field("user_agent", &self.user_agent)
+            .field("base_url_override", &self.base_url_override)
+            .field("sms_short_code", &self.sms_short_code)
+            .field("proxy", &self.proxy)
+            .field("pool_max_idle_per_host", &self.pool_max_idle_per_host)
+            .field("tcp_keepalive", &self.tcp_keepalive)
+            .field(
+                "default_headers",
+                &self.default_headers.keys().collect::<Vec<_>>(),
+            )
+            .field("on_retry", &self.on_retry.as_ref().map(|_| "Fn"))
+            .finish_non_exhaustive()
+    }
+}
+
 impl Config {
     /// Create a new configuration
     pub fn new<S: Into<String>>(api_key: S, username: S) -> Self {
@@ -123,13 +224,91 @@ impl Config {
             environment: Environment::Sandbox,
             timeout: Duration::from_secs(30),
             max_retries: 3,
+            retry_base_delay: Duration::from_secs(1),
+            retry_max_delay: Duration::from_secs(30),
+            rate_limit: None,
             user_agent: None,
+            base_url_override: None,
+            sms_short_code: None,
+            proxy: None,
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            default_headers: HeaderMap::new(),
+            on_retry: None,
             endpoint_map: EndpointMap,
         }
     }
 
+    /// Create a configuration from environment variables
+    ///
+    /// Reads `AFRICASTALKING_API_KEY` and `AFRICASTALKING_USERNAME`, plus an
+    /// optional `AFRICASTALKING_ENVIRONMENT` (`sandbox` or `production`,
+    /// defaulting to sandbox) and `AFRICASTALKING_SMS_SHORT_CODE`.
+    ///
+    /// Surrounding whitespace, such as a trailing newline from a secrets file,
+    /// is trimmed from every value.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// `from_env` reading variables through `lookup`
+    fn from_vars(lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let var = |name: &str| lookup(name).map(|value| value.trim().to_string());
+        let required = |name: &str| {
+            var(name).ok_or_else(|| AfricasTalkingError::config(format!("{name} config not set")))
+        };
+
+        let api_key = required("AFRICASTALKING_API_KEY")?;
+        let username = required("AFRICASTALKING_USERNAME")?;
+
+        let environment = match var("AFRICASTALKING_ENVIRONMENT") {
+            Some(env) => match env.to_lowercase().as_str() {
+                "" | "sandbox" => Environment::Sandbox,
+                "production" => Environment::Production,
+                other => {
+                    return Err(AfricasTalkingError::config(format!(
+                        "AFRICASTALKING_ENVIRONMENT must be `sandbox` or `production`, got `{other}`"
+                    )));
+                }
+            },
+            None => Environment::Sandbox,
+        };
+
+        let mut config = Self::new(api_key, username).environment(environment);
+        if let Some(short_code) = var("AFRICASTALKING_SMS_SHORT_CODE")
+            && !short_code.is_empty()
+        {
+            config = config.sender_id(short_code);
+        }
+
+        Ok(config)
+    }
+
     /// Build a full URL for a given endpoint path
+    ///
+    /// This is the only place hosts are chosen; the client delegates to it.
+    ///
+    /// ```rust
+    /// use africastalking::{Config, Environment};
+    ///
+    /// let sandbox = Config::new("key", "sandbox");
+    /// assert_eq!(
+    ///     sandbox.build_url("/mobile/data/request"),
+    ///     "https://bundles.sandbox.africastalking.com/mobile/data/request"
+    /// );
+    ///
+    /// let production = Config::new("key", "app").environment(Environment::Production);
+    /// assert_eq!(production.build_url("/call"), "https://voice.africastalking.com/call");
+    /// assert_eq!(
+    ///     production.build_url("/version1/messaging"),
+    ///     "https://api.africastalking.com/version1/messaging"
+    /// );
+    /// ```
     pub fn build_url(&self, path: &str) -> String {
+        if let Some(base_url) = &self.base_url_override {
+            return format!("{}{}", base_url.trim_end_matches('/'), path);
+        }
+
         let endpoint = self.endpoint_map.get(path);
         endpoint.build_url(&self.environment, path)
     }
@@ -152,12 +331,109 @@ impl Config {
         self
     }
 
+    /// Send all requests to a custom base URL, e.g. a local mock server
+    ///
+    /// When set, the per-service host selection (`bundles.`, `voice.`,
+    /// `content.` subdomains) is bypassed and every endpoint path is appended
+    /// to this URL as-is.
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base_url_override = Some(base_url.into());
+        self
+    }
+
+    /// Set the base delay for exponential retry backoff
+    pub fn retry_base_delay(mut self, delay: Duration) -> Self {
+        self.retry_base_delay = delay;
+        self
+    }
+
+    /// Set the maximum delay between retries
+    pub fn retry_max_delay(mut self, delay: Duration) -> Self {
+        self.retry_max_delay = delay;
+        self
+    }
+
+    /// Limit the client to a number of requests per second
+    ///
+    /// Requests wait for a free slot instead of relying on 429 retries. The
+    /// limit is shared by all clones of the client.
+    pub fn rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limit = Some(requests_per_second);
+        self
+    }
+
     /// Set custom user agent
     pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
         self.user_agent = Some(user_agent.into());
         self
     }
 
+    /// Set the sender ID (short code or alphanumeric) used for bulk SMS
+    pub fn sender_id<S: Into<String>>(mut self, sender_id: S) -> Self {
+        self.sms_short_code = Some(sender_id.into());
+        self
+    }
+
+    /// Route all requests through an HTTP(S) proxy, e.g. `http://proxy:8080`
+    pub fn proxy<S: Into<String>>(mut self, url: S) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Limit the idle connections kept open per host (unlimited by default)
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Send TCP keep-alive probes at this interval (disabled by default)
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Send extra headers with every request
+    ///
+    /// The `apikey`, `Accept` and `Content-Type` headers set by the SDK take
+    /// precedence over headers of the same name.
+    ///
+    /// ```rust
+    /// use africastalking::{Config, HeaderMap};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("X-Correlation-Id", "req-42".parse().unwrap());
+    /// let config = Config::new("key", "sandbox").default_headers(headers);
+    /// assert_eq!(config.default_headers["x-correlation-id"], "req-42");
+    /// ```
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// Run `hook` before each retry with the error and the attempt number,
+    /// starting at 1
+    ///
+    /// Requests are retried after connection errors, timeouts, and 429 or 5xx
+    /// responses.
+    ///
+    /// ```rust
+    /// use africastalking::Config;
+    /// use std::sync::{
+    ///     Arc,
+    ///     atomic::{AtomicU32, Ordering},
+    /// };
+    ///
+    /// let retries = Arc::new(AtomicU32::new(0));
+    /// let counter = retries.clone();
+    /// let config = Config::new("key", "sandbox").on_retry(Arc::new(move |_error, _attempt| {
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    /// }));
+    /// ```
+    pub fn on_retry(mut self, hook: RetryHook) -> Self {
+        self.on_retry = Some(hook);
+        self
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> Result<()> {
         if self.api_key.is_empty() {
@@ -168,12 +444,148 @@ impl Config {
             return Err(AfricasTalkingError::config("Username cannot be empty"));
         }
 
+        if self.rate_limit == Some(0) {
+            return Err(AfricasTalkingError::config(
+                "Rate limit must be greater than 0",
+            ));
+        }
+
         if self.timeout.as_secs() == 0 {
             return Err(AfricasTalkingError::config(
                 "Timeout must be greater than 0",
             ));
         }
 
+        if self.timeout > MAX_TIMEOUT {
+            return Err(AfricasTalkingError::config(format!(
+                "Timeout cannot exceed {} seconds",
+                MAX_TIMEOUT.as_secs()
+            )));
+        }
+
+        if self.max_retries > MAX_RETRIES {
+            return Err(AfricasTalkingError::config(format!(
+                "Max retries cannot exceed {MAX_RETRIES}"
+            )));
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn from_vars(vars: &[(&str, &str)]) -> Result<Config> {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        Config::from_vars(|name| vars.get(name).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn from_env_trims_values() {
+        let config = from_vars(&[
+            ("AFRICASTALKING_API_KEY", " atsk_123\n"),
+            ("AFRICASTALKING_USERNAME", "\tsandbox \r\n"),
+            ("AFRICASTALKING_ENVIRONMENT", " Production\n"),
+            ("AFRICASTALKING_SMS_SHORT_CODE", " 12345 "),
+        ])
+        .unwrap();
+        assert_eq!(config.api_key, "atsk_123");
+        assert_eq!(config.username, "sandbox");
+        assert!(matches!(config.environment, Environment::Production));
+        assert_eq!(config.sms_short_code.as_deref(), Some("12345"));
+    }
+
+    #[test]
+    fn from_env_requires_key_and_username() {
+        let error = from_vars(&[("AFRICASTALKING_USERNAME", "sandbox")]).unwrap_err();
+        assert!(error.to_string().contains("AFRICASTALKING_API_KEY"));
+
+        // Blank values are caught by validation once trimmed
+        let config = from_vars(&[
+            ("AFRICASTALKING_API_KEY", "  \n"),
+            ("AFRICASTALKING_USERNAME", "sandbox"),
+        ])
+        .unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(AfricasTalkingError::Config(_))
+        ));
+    }
+
+    fn custom() -> Environment {
+        Environment::Custom {
+            base_url: "http://gateway.internal:8080/".into(),
+            base_domain: "at.example.com".into(),
+        }
+    }
+
+    #[test]
+    fn custom_standard_urls_use_base_url() {
+        let config = Config::new("key", "app").environment(custom());
+        assert_eq!(
+            config.build_url("/version1/user"),
+            "http://gateway.internal:8080/version1/user"
+        );
+    }
+
+    #[test]
+    fn custom_service_urls_use_base_domain() {
+        let config = Config::new("key", "app").environment(custom());
+        assert_eq!(
+            config.build_url("/call"),
+            "https://voice.at.example.com/call"
+        );
+        assert_eq!(
+            config.build_url("/queueStatus"),
+            "https://voice.at.example.com/queueStatus"
+        );
+        assert_eq!(
+            config.build_url("/mobile/data/request"),
+            "https://bundles.at.example.com/mobile/data/request"
+        );
+    }
+
+    #[test]
+    fn hosted_standard_urls_are_unchanged() {
+        let config = Config::new("key", "app");
+        assert_eq!(
+            config.build_url("/version1/user"),
+            "https://api.sandbox.africastalking.com/version1/user"
+        );
+        let config = config.environment(Environment::Production);
+        assert_eq!(
+            config.build_url("/version1/user"),
+            "https://api.africastalking.com/version1/user"
+        );
+        assert_eq!(
+            config.build_url("/mobile/data/request"),
+            "https://bundles.africastalking.com/mobile/data/request"
+        );
+    }
+
+    #[test]
+    fn validate_accepts_the_timeout_and_retry_limits() {
+        let config = Config::new("key", "app")
+            .timeout(MAX_TIMEOUT)
+            .max_retries(MAX_RETRIES);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_values_past_the_limits() {
+        let config = Config::new("key", "app").timeout(MAX_TIMEOUT + Duration::from_secs(1));
+        assert!(matches!(
+            config.validate(),
+            Err(AfricasTalkingError::Config(_))
+        ));
+
+        let config = Config::new("key", "app").max_retries(MAX_RETRIES + 1);
+        assert!(matches!(
+            config.validate(),
+            Err(AfricasTalkingError::Config(_))
+        ));
+    }
+}
@@ -7,20 +7,40 @@ use serde::{Deserialize, Serialize};
 pub enum AfricasTalkingError {
     /// HTTP request failed
     #[error("HTTP request failed: {0}")]
-    Http(#[from] reqwest::Error),
+    Http(#[source] reqwest::Error),
 
     /// API returned an error response
     #[error("API error: {message} (code: {code})")]
     Api {
         message: String,
         code: String,
+        /// Extra detail sent by the API as `MoreInfo`
         more_info: Option<String>,
     },
 
+    /// A non-success response whose body isn't an AfricasTalking error
+    /// object, such as a gateway's HTML page or an unrecognised JSON body
+    #[error("HTTP {status}: {message}")]
+    HttpStatus {
+        status: u16,
+        /// Short single-line summary of the body
+        message: String,
+        /// The full response body
+        body: String,
+    },
+
     /// JSON serialization/deserialization error
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    /// A successful response body did not match the expected schema
+    #[error("Unexpected response body: {source}")]
+    UnexpectedResponse {
+        body: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
     /// Configuration error
     #[error("Configuration error: {0}")]
     Config(String),
@@ -60,6 +80,16 @@ pub struct ApiErrorResponse {
     pub more_info: Option<String>,
 }
 
+impl From<reqwest::Error> for AfricasTalkingError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            AfricasTalkingError::Timeout
+        } else {
+            AfricasTalkingError::Http(error)
+        }
+    }
+}
+
 impl AfricasTalkingError {
     /// Create an API error from response
     pub fn api_error(message: String, code: String, more_info: Option<String>) -> Self {
@@ -80,6 +110,79 @@ impl AfricasTalkingError {
         Self::Config(message.into())
     }
 
+    /// Parse the `more_info` of an `Api` error as JSON
+    ///
+    /// Returns `None` for other errors and when `more_info` is missing or
+    /// isn't JSON; the raw string stays available on the variant.
+    ///
+    /// ```rust
+    /// use africastalking::AfricasTalkingError;
+    ///
+    /// let error = AfricasTalkingError::api_error(
+    ///     "Invalid request".into(),
+    ///     "400".into(),
+    ///     Some(r#"{"field": "phoneNumber"}"#.into()),
+    /// );
+    /// assert_eq!(error.more_info_json().unwrap()["field"], "phoneNumber");
+    ///
+    /// let error = AfricasTalkingError::api_error(
+    ///     "Invalid request".into(),
+    ///     "400".into(),
+    ///     Some("See the docs".into()),
+    /// );
+    /// assert!(error.more_info_json().is_none());
+    /// ```
+    pub fn more_info_json(&self) -> Option<serde_json::Value> {
+        match self {
+            AfricasTalkingError::Api {
+                more_info: Some(more_info),
+                ..
+            } => serde_json::from_str(more_info).ok(),
+            _ => None,
+        }
+    }
+
+    /// The HTTP status code behind the error, if there is one
+    ///
+    /// `Api` codes are parsed from their leading digits, so both `"503"` and
+    /// `"503 Service Unavailable"` give `Some(503)`.
+    ///
+    /// ```rust
+    /// use africastalking::AfricasTalkingError;
+    ///
+    /// let error = AfricasTalkingError::api_error("Bad".into(), "400".into(), None);
+    /// assert_eq!(error.status_code(), Some(400));
+    /// assert!(error.is_client_error() && !error.is_server_error());
+    ///
+    /// let error = AfricasTalkingError::api_error("Down".into(), "503 Service Unavailable".into(), None);
+    /// assert!(error.is_server_error());
+    ///
+    /// let error = AfricasTalkingError::api_error("Failed".into(), "InvalidSenderId".into(), None);
+    /// assert_eq!(error.status_code(), None);
+    /// ```
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            AfricasTalkingError::Api { code, .. } => {
+                let digits = code.split_whitespace().next()?;
+                digits.parse().ok()
+            }
+            AfricasTalkingError::HttpStatus { status, .. } => Some(*status),
+            AfricasTalkingError::Http(error) => error.status().map(|status| status.as_u16()),
+            AfricasTalkingError::RateLimit { .. } => Some(429),
+            _ => None,
+        }
+    }
+
+    /// Check if the error came from a 4xx response
+    pub fn is_client_error(&self) -> bool {
+        matches!(self.status_code(), Some(400..=499))
+    }
+
+    /// Check if the error came from a 5xx response
+    pub fn is_server_error(&self) -> bool {
+        matches!(self.status_code(), Some(500..=599))
+    }
+
     /// Check if error is retryable
     pub fn is_retryable(&self) -> bool {
         matches!(
@@ -17,12 +17,17 @@ pub mod client;
 pub mod config;
 pub mod error;
 pub mod modules;
+mod rate_limit;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod types;
+pub mod webhooks;
 
 // Re-export main types for easier usage
 pub use client::AfricasTalkingClient;
-pub use config::{Config, Environment};
+pub use config::{Config, Environment, RetryHook};
 pub use error::{AfricasTalkingError, Result};
+pub use reqwest::header::{HeaderMap, HeaderValue};
 pub use types::*;
 
 // Re-export modules for direct access
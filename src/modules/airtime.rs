@@ -1,8 +1,13 @@
 // src/modules/airtime.rs
 //! Airtime module implementation
 
-use crate::{client::AfricasTalkingClient, error::Result, Currency};
+use crate::{
+    Currency, PhoneNumber,
+    client::AfricasTalkingClient,
+    error::{AfricasTalkingError, Result},
+};
 use serde::{Deserialize, Serialize};
+use std::{convert::Infallible, str::FromStr};
 
 /// Airtime module for sending airtime
 #[derive(Debug, Clone)]
@@ -17,13 +22,98 @@ impl AirtimeModule {
     
     /// Send airtime to recipients
     pub async fn send(&self, request: SendAirtimeRequest) -> Result<SendAirtimeResponse> {
-        self.client.post("/version1/airtime/send", &request).await
+        if request.recipients.is_empty() {
+            return Err(AfricasTalkingError::validation(
+                "Airtime requests need at least one recipient",
+            ));
+        }
+
+        self.client
+            .post_form("/version1/airtime/send", &request)
+            .await
     }
 }
 
 #[derive(Debug, Serialize)]
 pub struct SendAirtimeRequest {
+    /// Sent as a JSON array string in the `recipients` form field, which is
+    /// how `/version1/airtime/send` takes its recipient list
     pub recipients: Vec<AirtimeRecipient>,
+    /// How many times AfricasTalking retries a failed top-up
+    #[serde(rename = "maxNumRetry", skip_serializing_if = "Option::is_none")]
+    pub max_num_retry: Option<u32>,
+}
+
+impl SendAirtimeRequest {
+    /// Create a request for the given recipients
+    ///
+    /// ```rust
+    /// use africastalking::{airtime::{AirtimeRecipient, SendAirtimeRequest}, Currency};
+    ///
+    /// let request = SendAirtimeRequest::new(vec![AirtimeRecipient::new("+254711000000", "100", Currency::Kes)])
+    ///     .push(AirtimeRecipient::new("+254722000000", "50", Currency::Kes));
+    ///
+    /// let json = serde_json::to_value(&request).unwrap();
+    /// assert_eq!(json["recipients"][1]["phoneNumber"], "+254722000000");
+    /// assert_eq!(json["recipients"][1]["amount"], "50");
+    /// ```
+    pub fn new(recipients: Vec<AirtimeRecipient>) -> Self {
+        Self {
+            recipients,
+            max_num_retry: None,
+        }
+    }
+
+    /// Add a recipient
+    pub fn push(mut self, recipient: AirtimeRecipient) -> Self {
+        self.recipients.push(recipient);
+        self
+    }
+
+    /// Start building a request
+    pub fn builder() -> SendAirtimeRequestBuilder {
+        SendAirtimeRequestBuilder::default()
+    }
+}
+
+impl From<Vec<AirtimeRecipient>> for SendAirtimeRequest {
+    fn from(recipients: Vec<AirtimeRecipient>) -> Self {
+        Self::new(recipients)
+    }
+}
+
+/// Builder for [`SendAirtimeRequest`]
+#[derive(Debug, Default)]
+pub struct SendAirtimeRequestBuilder {
+    recipients: Vec<AirtimeRecipient>,
+    max_num_retry: Option<u32>,
+}
+
+impl SendAirtimeRequestBuilder {
+    /// Add a recipient
+    pub fn recipient(mut self, recipient: AirtimeRecipient) -> Self {
+        self.recipients.push(recipient);
+        self
+    }
+
+    /// Add several recipients
+    pub fn recipients<I: IntoIterator<Item = AirtimeRecipient>>(mut self, recipients: I) -> Self {
+        self.recipients.extend(recipients);
+        self
+    }
+
+    /// Set how many times a failed top-up is retried
+    pub fn max_num_retry(mut self, max_num_retry: u32) -> Self {
+        self.max_num_retry = Some(max_num_retry);
+        self
+    }
+
+    pub fn build(self) -> SendAirtimeRequest {
+        SendAirtimeRequest {
+            recipients: self.recipients,
+            max_num_retry: self.max_num_retry,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -43,12 +133,128 @@ impl AirtimeRecipient {
             amount: amount.into(),
         }
     }
+
+    /// Create a recipient, normalizing the phone number to E.164 first
+    ///
+    /// ```rust
+    /// use africastalking::{airtime::AirtimeRecipient, Currency};
+    ///
+    /// let recipient = AirtimeRecipient::new_checked("0712345678", "100", Currency::Kes, "KE").unwrap();
+    /// assert_eq!(recipient.phone_number, "+254712345678");
+    /// ```
+    pub fn new_checked<S: Into<String>>(
+        phone_number: S,
+        amount: S,
+        currency: Currency,
+        default_country: &str,
+    ) -> Result<Self> {
+        let phone_number = PhoneNumber::parse(&phone_number.into(), default_country)?;
+        Ok(Self::new(phone_number.as_e164(), amount.into(), currency))
+    }
+
+    /// Create a recipient from a numeric amount
+    ///
+    /// Whole amounts are sent without decimals and others with two, never
+    /// with thousands separators. The amount must be greater than zero.
+    ///
+    /// ```rust
+    /// use africastalking::airtime::AirtimeRecipient;
+    ///
+    /// let recipient = AirtimeRecipient::kes("+254711000000", 100.0).unwrap();
+    /// assert_eq!(recipient.amount, "100");
+    /// assert_eq!(recipient.currency_code, "KES");
+    ///
+    /// assert_eq!(AirtimeRecipient::usd("+254711000000", 1.5).unwrap().amount, "1.50");
+    /// assert!(AirtimeRecipient::kes("+254711000000", 0.0).is_err());
+    /// ```
+    pub fn with_amount<S: Into<String>>(
+        phone_number: S,
+        amount: f64,
+        currency: Currency,
+    ) -> Result<Self> {
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(AfricasTalkingError::validation(format!(
+                "Airtime amount must be greater than 0, got {amount}"
+            )));
+        }
+
+        let amount = if amount.fract() == 0.0 {
+            format!("{amount:.0}")
+        } else {
+            format!("{amount:.2}")
+        };
+        Ok(Self {
+            phone_number: phone_number.into(),
+            currency_code: currency.as_str().to_string(),
+            amount,
+        })
+    }
+
+    /// Top up `phone_number` with an amount in Kenyan shillings
+    pub fn kes<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Kes)
+    }
+
+    /// Top up `phone_number` with an amount in US dollars
+    pub fn usd<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Usd)
+    }
+
+    /// Top up `phone_number` with an amount in Ugandan shillings
+    pub fn ugx<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Ugx)
+    }
+
+    /// Top up `phone_number` with an amount in Tanzanian shillings
+    pub fn tzs<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Tzs)
+    }
+
+    /// Top up `phone_number` with an amount in Rwandan francs
+    pub fn rwf<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Rwf)
+    }
+
+    /// Top up `phone_number` with an amount in Zambian kwacha
+    pub fn zmw<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Zmw)
+    }
+
+    /// Top up `phone_number` with an amount in Nigerian naira
+    pub fn ngn<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Ngn)
+    }
+
+    /// Top up `phone_number` with an amount in Ghanaian cedis
+    pub fn ghs<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Ghs)
+    }
+
+    /// Top up `phone_number` with an amount in Ethiopian birr
+    pub fn etb<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Etb)
+    }
+
+    /// Top up `phone_number` with an amount in Malawian kwacha
+    pub fn mwk<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Mwk)
+    }
+
+    /// Top up `phone_number` with an amount in West African CFA francs
+    pub fn xof<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Xof)
+    }
+
+    /// Top up `phone_number` with an amount in Central African CFA francs
+    pub fn xaf<S: Into<String>>(phone_number: S, amount: f64) -> Result<Self> {
+        Self::with_amount(phone_number, amount, Currency::Xaf)
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct SendAirtimeResponse {
     #[serde(rename = "errorMessage")]
-    pub error_message: String,
+    pub error_message: Option<String>,
     #[serde(rename = "numSent")]
     pub num_sent: u32,
     #[serde(rename = "totalAmount")]
@@ -72,5 +278,195 @@ pub struct AirtimeResponse {
     #[serde(rename = "discount")]
     pub discount: String,
     #[serde(rename = "errorMessage")]
-    pub error_message: String,
+    pub error_message: Option<String>,
+}
+
+impl AirtimeResponse {
+    /// The recipient's `status` as a typed status
+    pub fn parsed_status(&self) -> AirtimeStatus {
+        AirtimeStatus::from(self.status.as_str())
+    }
+}
+
+/// Per-recipient status returned when sending airtime
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AirtimeStatus {
+    Sent,
+    Success,
+    Queued,
+    Failed,
+    /// A status not known to this version of the SDK
+    Unknown(String),
+}
+
+impl AirtimeStatus {
+    /// Whether the airtime was accepted for delivery
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self,
+            AirtimeStatus::Sent | AirtimeStatus::Success | AirtimeStatus::Queued
+        )
+    }
+}
+
+impl FromStr for AirtimeStatus {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(AirtimeStatus::from(s))
+    }
+}
+
+impl From<&str> for AirtimeStatus {
+    fn from(status: &str) -> Self {
+        match status {
+            "Sent" => AirtimeStatus::Sent,
+            "Success" => AirtimeStatus::Success,
+            "Queued" => AirtimeStatus::Queued,
+            "Failed" => AirtimeStatus::Failed,
+            other => AirtimeStatus::Unknown(other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockAfricasTalking;
+    use std::collections::HashMap;
+
+    #[test]
+    fn new_checked_normalizes_the_phone_number_in_the_payload() {
+        let recipient =
+            AirtimeRecipient::new_checked("0712345678", "100", Currency::Kes, "KE").unwrap();
+        let json = serde_json::to_value(&recipient).unwrap();
+        assert_eq!(json["phoneNumber"], "+254712345678");
+        assert_eq!(json["currencyCode"], "KES");
+    }
+
+    #[test]
+    fn new_checked_rejects_malformed_numbers() {
+        assert!(AirtimeRecipient::new_checked("07l2345678", "100", Currency::Kes, "KE").is_err());
+    }
+
+    fn response(entries: serde_json::Value, num_sent: u32) -> SendAirtimeResponse {
+        serde_json::from_value(serde_json::json!({
+            "errorMessage": "None",
+            "numSent": num_sent,
+            "totalAmount": "KES 100.0000",
+            "totalDiscount": "KES 4.0000",
+            "responses": entries
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn parsed_status_reads_a_successful_send() {
+        let response = response(
+            serde_json::json!([{
+                "phoneNumber": "+254711082000",
+                "errorMessage": "None",
+                "amount": "KES 100.0000",
+                "status": "Sent",
+                "requestId": "ATQid_1",
+                "discount": "KES 4.0000"
+            }]),
+            1,
+        );
+        let status = response.responses[0].parsed_status();
+        assert_eq!(status, AirtimeStatus::Sent);
+        assert!(status.is_success());
+    }
+
+    #[test]
+    fn parsed_status_reads_a_partial_failure() {
+        let response = response(
+            serde_json::json!([
+                {
+                    "phoneNumber": "+254711082000",
+                    "errorMessage": "None",
+                    "amount": "KES 100.0000",
+                    "status": "Sent",
+                    "requestId": "ATQid_1",
+                    "discount": "KES 4.0000"
+                },
+                {
+                    "phoneNumber": "+254711082001",
+                    "errorMessage": "Value Outside The Allowed Limits",
+                    "amount": "KES 100000.0000",
+                    "status": "Failed",
+                    "requestId": "None",
+                    "discount": "KES 0.0000"
+                }
+            ]),
+            1,
+        );
+        let statuses: Vec<_> = response
+            .responses
+            .iter()
+            .map(AirtimeResponse::parsed_status)
+            .collect();
+        assert_eq!(statuses, [AirtimeStatus::Sent, AirtimeStatus::Failed]);
+        assert!(!statuses[1].is_success());
+        assert_eq!(
+            response.responses[1].error_message.as_deref(),
+            Some("Value Outside The Allowed Limits")
+        );
+    }
+
+    #[test]
+    fn unknown_statuses_are_kept() {
+        let status: AirtimeStatus = "Reversed".parse().unwrap();
+        assert_eq!(status, AirtimeStatus::Unknown("Reversed".into()));
+        assert!(!status.is_success());
+    }
+
+    #[tokio::test]
+    async fn send_includes_max_num_retry_only_when_set() {
+        let mock = MockAfricasTalking::start().await;
+        mock.send_airtime(serde_json::json!({
+            "errorMessage": "None",
+            "numSent": 1,
+            "totalAmount": "KES 100.0000",
+            "totalDiscount": "KES 4.0000",
+            "responses": []
+        }))
+        .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+        let recipient = || AirtimeRecipient::kes("+254711082000", 100.0).unwrap();
+
+        let request = SendAirtimeRequest::builder()
+            .recipient(recipient())
+            .max_num_retry(3)
+            .build();
+        client.airtime().send(request).await.unwrap();
+        client
+            .airtime()
+            .send(SendAirtimeRequest::new(vec![recipient()]))
+            .await
+            .unwrap();
+
+        let requests = mock.server().received_requests().await.unwrap();
+        let fields: Vec<HashMap<String, String>> = requests
+            .iter()
+            .map(|request| serde_urlencoded::from_bytes(&request.body).unwrap())
+            .collect();
+        assert_eq!(fields[0]["maxNumRetry"], "3");
+        assert!(!fields[1].contains_key("maxNumRetry"));
+    }
+
+    #[tokio::test]
+    async fn send_rejects_an_empty_recipient_list() {
+        let mock = MockAfricasTalking::start().await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let error = client
+            .airtime()
+            .send(SendAirtimeRequest::builder().max_num_retry(3).build())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AfricasTalkingError::Validation(_)));
+        assert!(mock.server().received_requests().await.unwrap().is_empty());
+    }
 }
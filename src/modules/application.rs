@@ -1,7 +1,7 @@
 // src/modules/application.rs
 //! Application module implementation
 
-use crate::{client::AfricasTalkingClient, error::Result};
+use crate::{Currency, client::AfricasTalkingClient, error::Result, types::parse_amount};
 use serde::Deserialize;
 
 /// Application module for getting app data
@@ -31,3 +31,47 @@ pub struct ApplicationDataResponse {
 pub struct UserData {
     pub balance: String,
 }
+
+impl UserData {
+    /// Parse the balance (e.g. `"KES 1234.50"`) into its currency and amount
+    pub fn parsed_balance(&self) -> Result<(Currency, f64)> {
+        parse_amount(&self.balance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AfricasTalkingError;
+
+    fn user_data(balance: &str) -> UserData {
+        UserData {
+            balance: balance.to_string(),
+        }
+    }
+
+    #[test]
+    fn parsed_balance_reads_currency_and_amount() {
+        assert_eq!(
+            user_data("KES 1234.50").parsed_balance().unwrap(),
+            (Currency::Kes, 1234.5)
+        );
+        assert_eq!(
+            user_data("USD 0.00").parsed_balance().unwrap(),
+            (Currency::Usd, 0.0)
+        );
+    }
+
+    #[test]
+    fn parsed_balance_rejects_malformed_balances() {
+        for balance in ["KES", "1234.50", "KES twelve", "XYZ 10.00", ""] {
+            assert!(
+                matches!(
+                    user_data(balance).parsed_balance(),
+                    Err(AfricasTalkingError::Validation(_))
+                ),
+                "{balance:?} should not parse"
+            );
+        }
+    }
+}
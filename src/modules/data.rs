@@ -1,6 +1,11 @@
-use std::fmt;
+use std::{convert::Infallible, fmt, str::FromStr};
 
-use crate::{client::AfricasTalkingClient, error::Result};
+use crate::{
+    Currency,
+    client::AfricasTalkingClient,
+    error::{AfricasTalkingError, Result},
+    types::{AtResponse, parse_amount},
+};
 use serde::{Deserialize, Serialize};
 
 /// SMS module for sending and managing SMS messages
@@ -34,7 +39,7 @@ impl DataModule {
     }
 
     // Query wallet balance
-    pub async fn query_wallet_balance(&self) -> Result<QueryWalletBalanceResponce> {
+    pub async fn query_wallet_balance(&self) -> Result<WalletBalanceResponse> {
         let user_name = self.client.config.username.clone();
         let endpoint = format!("/query/wallet/balance?username={user_name}");
         self.client.get(&endpoint).await
@@ -57,11 +62,16 @@ pub struct RecipientMetadata {
 }
 
 // The available data validity classes.
-#[derive(Debug, Serialize, Deserialize)]
+//
+// These are the periods listed for `validity` in the mobile data docs:
+// https://developers.africastalking.com/docs/data/request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataValidity {
     Day,
     Week,
+    BiWeek,
     Month,
+    Quarterly,
 }
 
 impl fmt::Display for DataValidity {
@@ -69,14 +79,33 @@ impl fmt::Display for DataValidity {
         let validity_str = match self {
             DataValidity::Day => "Day",
             DataValidity::Week => "Week",
+            DataValidity::BiWeek => "BiWeek",
             DataValidity::Month => "Month",
+            DataValidity::Quarterly => "Quarterly",
         };
         write!(f, "{}", validity_str)
     }
 }
 
+impl FromStr for DataValidity {
+    type Err = AfricasTalkingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "Day" => Ok(DataValidity::Day),
+            "Week" => Ok(DataValidity::Week),
+            "BiWeek" => Ok(DataValidity::BiWeek),
+            "Month" => Ok(DataValidity::Month),
+            "Quarterly" => Ok(DataValidity::Quarterly),
+            other => Err(AfricasTalkingError::validation(format!(
+                "Unknown data validity: {other}"
+            ))),
+        }
+    }
+}
+
 // The avaibale data packages/units.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataUnits {
     MB,
     GB,
@@ -92,6 +121,20 @@ impl fmt::Display for DataUnits {
     }
 }
 
+impl FromStr for DataUnits {
+    type Err = AfricasTalkingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "MB" => Ok(DataUnits::MB),
+            "GB" => Ok(DataUnits::GB),
+            other => Err(AfricasTalkingError::validation(format!(
+                "Unknown data unit: {other}"
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct Recipient {
     #[serde(rename = "phoneNumber")]
@@ -125,6 +168,46 @@ pub struct MobileDataResponse {
     pub value: Option<String>,
 }
 
+impl MobileDataResponse {
+    /// The entry's `status` as a typed status, if present
+    pub fn parsed_status(&self) -> Option<MobileDataStatus> {
+        self.status.as_deref().map(MobileDataStatus::from)
+    }
+}
+
+/// Per-recipient status returned when sending mobile data
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MobileDataStatus {
+    Queued,
+    Success,
+    InvalidRequest,
+    NotSupported,
+    Failed,
+    /// A status not known to this version of the SDK
+    Unknown(String),
+}
+
+impl MobileDataStatus {
+    /// Whether the bundle was accepted for delivery
+    pub fn is_success(&self) -> bool {
+        matches!(self, MobileDataStatus::Queued | MobileDataStatus::Success)
+    }
+}
+
+impl From<&str> for MobileDataStatus {
+    fn from(status: &str) -> Self {
+        match status {
+            "Queued" => MobileDataStatus::Queued,
+            "Success" => MobileDataStatus::Success,
+            "InvalidRequest" => MobileDataStatus::InvalidRequest,
+            "NotSupported" => MobileDataStatus::NotSupported,
+            "Failed" => MobileDataStatus::Failed,
+            other => MobileDataStatus::Unknown(other.to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MobileDataResponseList {
     #[serde(default)]
@@ -133,11 +216,33 @@ pub struct MobileDataResponseList {
     pub error_message: Option<String>,
 }
 
+impl AtResponse for MobileDataResponseList {
+    fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+}
+
+impl MobileDataResponseList {
+    /// Whether there was no top-level error and every entry was accepted
+    ///
+    /// An empty `entries` list gives `false`: no bundle was sent, so nothing
+    /// succeeded.
+    pub fn all_succeeded(&self) -> bool {
+        !self.is_error()
+            && !self.entries.is_empty()
+            && self.entries.iter().all(|entry| {
+                entry
+                    .parsed_status()
+                    .is_some_and(|status| status.is_success())
+            })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FindTransactionResponse {
     pub status: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub data: Option<MobileDataResponse>,
+    pub data: Option<FindTrandactionResponseData>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -157,7 +262,7 @@ pub struct FindTrandactionResponseData {
     pub transaction_fee: String,
     #[serde(rename = "providerMetadata")]
     pub provider_metadata: FindTrandactionResponseProviderMetadata,
-    pub stratus: String,
+    pub status: TransactionStatus,
     #[serde(rename = "productName")]
     pub product_name: String,
     pub category: String,
@@ -171,6 +276,61 @@ pub struct FindTrandactionResponseData {
     pub creation_time: String,
 }
 
+/// Status of a mobile data transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+#[non_exhaustive]
+pub enum TransactionStatus {
+    Success,
+    Failed,
+    Pending,
+    Refunded,
+    /// A status not known to this version of the SDK
+    Unknown(String),
+}
+
+impl TransactionStatus {
+    /// The status as sent by AfricasTalking
+    pub fn as_str(&self) -> &str {
+        match self {
+            TransactionStatus::Success => "Success",
+            TransactionStatus::Failed => "Failed",
+            TransactionStatus::Pending => "Pending",
+            TransactionStatus::Refunded => "Refunded",
+            TransactionStatus::Unknown(status) => status,
+        }
+    }
+}
+
+impl FromStr for TransactionStatus {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(TransactionStatus::from(s.to_string()))
+    }
+}
+
+impl From<String> for TransactionStatus {
+    fn from(status: String) -> Self {
+        match status.as_str() {
+            "Success" => TransactionStatus::Success,
+            "Failed" => TransactionStatus::Failed,
+            "Pending" => TransactionStatus::Pending,
+            "Refunded" => TransactionStatus::Refunded,
+            _ => TransactionStatus::Unknown(status),
+        }
+    }
+}
+
+impl From<TransactionStatus> for String {
+    fn from(status: TransactionStatus) -> Self {
+        match status {
+            TransactionStatus::Unknown(status) => status,
+            other => other.as_str().to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FindTrandactionResponseRequestMetadata {
     pub reason: String,
@@ -178,7 +338,7 @@ pub struct FindTrandactionResponseRequestMetadata {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FindTrandactionResponseProviderMetadata {
-    #[serde(rename = "recipientRegistred")]
+    #[serde(rename = "recipientRegistred", alias = "recipientIsRegistered")]
     pub recipient_registred: String,
     #[serde(rename = "recipientName")]
     pub recipient_name: String,
@@ -186,9 +346,194 @@ pub struct FindTrandactionResponseProviderMetadata {
 
 // Wallet balance response structure
 #[derive(Debug, Serialize, Deserialize)]
-pub struct QueryWalletBalanceResponce {
+pub struct WalletBalanceResponse {
     pub status: String,
     pub balance: String,
     #[serde(rename = "errorMessage")]
     pub error_message: Option<String>,
 }
+
+impl WalletBalanceResponse {
+    /// Parse the balance (e.g. `"KES 1234.50"`) into its currency and amount
+    pub fn parsed_balance(&self) -> Result<(Currency, f64)> {
+        parse_amount(&self.balance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn data_response(body: serde_json::Value) -> MobileDataResponseList {
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn entry(status: &str) -> serde_json::Value {
+        json!({
+            "phoneNumber": "+254711082000",
+            "provider": "Safaricom",
+            "status": status,
+            "transactionId": "ATPid_1",
+            "value": "KES 100.0000"
+        })
+    }
+
+    #[test]
+    fn all_succeeded_accepts_queued_and_successful_entries() {
+        let response = data_response(json!({
+            "entries": [entry("Queued"), entry("Success")]
+        }));
+        assert_eq!(
+            response.entries[0].parsed_status(),
+            Some(MobileDataStatus::Queued)
+        );
+        assert!(response.all_succeeded());
+
+        let response = data_response(json!({
+            "entries": [entry("Queued"), entry("InvalidRequest")]
+        }));
+        assert_eq!(
+            response.entries[1].parsed_status(),
+            Some(MobileDataStatus::InvalidRequest)
+        );
+        assert!(!response.all_succeeded());
+    }
+
+    #[test]
+    fn all_succeeded_is_false_for_errors_and_empty_lists() {
+        let response = data_response(json!({
+            "entries": [entry("Queued")],
+            "errorMessage": "Insufficient balance"
+        }));
+        assert!(response.is_error());
+        assert!(!response.all_succeeded());
+
+        let response = data_response(json!({ "entries": [] }));
+        assert!(!response.is_error());
+        assert!(!response.all_succeeded());
+
+        let response =
+            data_response(json!({ "entries": [entry("Success")], "errorMessage": "None" }));
+        assert!(response.all_succeeded());
+    }
+
+    #[test]
+    fn data_validity_round_trips_every_variant() {
+        for (validity, name) in [
+            (DataValidity::Day, "Day"),
+            (DataValidity::Week, "Week"),
+            (DataValidity::BiWeek, "BiWeek"),
+            (DataValidity::Month, "Month"),
+            (DataValidity::Quarterly, "Quarterly"),
+        ] {
+            assert_eq!(validity.to_string(), name);
+            assert_eq!(name.parse::<DataValidity>().unwrap(), validity);
+            assert_eq!(serde_json::to_value(validity).unwrap(), json!(name));
+            assert_eq!(
+                serde_json::from_value::<DataValidity>(json!(name)).unwrap(),
+                validity
+            );
+        }
+        assert!(matches!(
+            "Annually".parse::<DataValidity>(),
+            Err(AfricasTalkingError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn data_units_round_trip_every_variant() {
+        for (unit, name) in [(DataUnits::MB, "MB"), (DataUnits::GB, "GB")] {
+            assert_eq!(unit.to_string(), name);
+            assert_eq!(name.parse::<DataUnits>().unwrap(), unit);
+            assert_eq!(serde_json::to_value(unit).unwrap(), json!(name));
+            assert_eq!(
+                serde_json::from_value::<DataUnits>(json!(name)).unwrap(),
+                unit
+            );
+        }
+        assert!(matches!(
+            "KB".parse::<DataUnits>(),
+            Err(AfricasTalkingError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn find_transaction_response_reads_a_sample_payload() {
+        let response: FindTransactionResponse = serde_json::from_value(json!({
+            "status": "Success",
+            "data": {
+                "requestMetadata": { "reason": "Testing" },
+                "sourceType": "Wallet",
+                "source": "PaymentWallet",
+                "provider": "Mpesa",
+                "destinationType": "PhoneNumber",
+                "description": "The service request is processed successfully.",
+                "providerChannel": "824879",
+                "transactionFee": "KES 1.0000",
+                "providerRefId": "SAMPLE_MPESA_CODE",
+                "providerMetadata": {
+                    "recipientIsRegistered": "true",
+                    "recipientName": "John Doe"
+                },
+                "status": "Success",
+                "productName": "testing",
+                "category": "MobileData",
+                "transactionDate": "12.05.2021 17:30:34",
+                "destination": "+254711082000",
+                "value": "KES 2900.0000",
+                "transactionId": "ATPid_b9379b671fee8ccf24b2c74f94da0ceb",
+                "creationTime": "2021-05-12 14:30:34"
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(response.status, "Success");
+        let data = response.data.unwrap();
+        assert_eq!(data.status, TransactionStatus::Success);
+        assert_eq!(
+            data.transaction_id,
+            "ATPid_b9379b671fee8ccf24b2c74f94da0ceb"
+        );
+        assert_eq!(data.request_metadata.reason, "Testing");
+        assert_eq!(data.provider_metadata.recipient_registred, "true");
+        assert_eq!(data.provider_metadata.recipient_name, "John Doe");
+        assert_eq!(data.destination, "+254711082000");
+        assert_eq!(data.value, "KES 2900.0000");
+    }
+
+    #[test]
+    fn find_transaction_response_allows_missing_data() {
+        let response: FindTransactionResponse =
+            serde_json::from_value(json!({ "status": "Failed" })).unwrap();
+        assert_eq!(response.status, "Failed");
+        assert!(response.data.is_none());
+    }
+
+    #[tokio::test]
+    async fn query_wallet_balance_reads_a_sample_payload() {
+        use crate::test_util::MockAfricasTalking;
+        use wiremock::{
+            Mock, ResponseTemplate,
+            matchers::{method, path, query_param},
+        };
+
+        let mock = MockAfricasTalking::start().await;
+        Mock::given(method("GET"))
+            .and(path("/query/wallet/balance"))
+            .and(query_param("username", "sandbox"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "status": "Success", "balance": "KES 1000.00" })),
+            )
+            .expect(1)
+            .mount(mock.server())
+            .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let response = client.data().query_wallet_balance().await.unwrap();
+        assert_eq!(response.status, "Success");
+        assert!(response.error_message.is_none());
+        assert_eq!(response.parsed_balance().unwrap(), (Currency::Kes, 1000.0));
+    }
+}
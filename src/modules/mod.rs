@@ -3,19 +3,21 @@ pub mod application;
 /// Module implementations for AfricasTalking services
 pub mod sms;
 pub mod data;
+pub mod payments;
+pub mod ussd;
+pub mod voice;
 
 // Re-export modules
 pub use airtime::AirtimeModule;
 pub use application::ApplicationModule;
 pub use sms::SmsModule;
 pub use data::DataModule;
+pub use payments::PaymentsModule;
+pub use voice::VoiceModule;
 
 // TODO: split modules into optional features
 
 // Modules not implemented
-// pub mod voice;
-// pub mod payments;
 // pub mod data;
 // pub mod chat;
 // pub mod insights;
-// pub mod ussd;
@@ -1,7 +1,18 @@
-/// Payments module implementation
+//! Payments module implementation
 
-use crate::{client::AfricasTalkingClient, error::Result, AfricasTalkingError, Currency};
+use crate::{
+    AfricasTalkingError, Currency,
+    client::AfricasTalkingClient,
+    error::Result,
+    types::{Pagination, parse_amount},
+};
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+#[cfg(feature = "stream")]
+use futures_util::stream::{self, Stream};
+#[cfg(feature = "stream")]
+use std::collections::VecDeque;
 
 /// Payments module for handling mobile and bank payments
 #[derive(Debug, Clone)]
@@ -13,52 +24,94 @@ impl PaymentsModule {
     pub(crate) fn new(client: AfricasTalkingClient) -> Self {
         Self { client }
     }
-    
-    /// Mobile checkout (B2C)
-    pub async fn mobile_checkout(&self, request: MobileCheckoutRequest) -> Result<MobileCheckoutResponse> {
-        self.client.post("/version1/payments/mobile/checkout/request", &request).await
+
+    /// Mobile checkout (C2B)
+    pub async fn mobile_checkout(
+        &self,
+        request: MobileCheckoutRequest,
+    ) -> Result<MobileCheckoutResponse> {
+        self.client
+            .post_form("/version1/payments/mobile/checkout/request", &request)
+            .await
+    }
+
+    /// Mobile B2C payment
+    pub async fn mobile_b2c(&self, request: MobileB2CRequest) -> Result<MobileB2CResponse> {
+        self.client
+            .post_form("/version1/payments/mobile/b2c/request", &request)
+            .await
     }
-    
+
     /// Mobile B2B payment
     pub async fn mobile_b2b(&self, request: MobileB2BRequest) -> Result<MobileB2BResponse> {
-        self.client.post("/version1/payments/mobile/b2b/request", &request).await
+        self.client
+            .post_form("/version1/payments/mobile/b2b/request", &request)
+            .await
     }
-    
+
     /// Bank checkout
-    pub async fn bank_checkout(&self, request: BankCheckoutRequest) -> Result<BankCheckoutResponse> {
-        self.client.post("/version1/payments/bank/checkout/request", &request).await
+    pub async fn bank_checkout(
+        &self,
+        request: BankCheckoutRequest,
+    ) -> Result<BankCheckoutResponse> {
+        self.client
+            .post_form("/version1/payments/bank/checkout/request", &request)
+            .await
     }
-    
-    /// Bank transfer
-    pub async fn bank_transfer(&self, request: BankTransferRequest) -> Result<BankTransferResponse> {
-        self.client.post("/version1/payments/bank/transfer", &request).await
+
+    /// Bank transfer (B2C) to one or more bank accounts
+    pub async fn bank_transfer(
+        &self,
+        request: BankTransferRequest,
+    ) -> Result<BankTransferResponse> {
+        self.client
+            .post_form("/version1/payments/bank/transfer", &request)
+            .await
     }
-    
-    /// Card checkout
-    pub async fn card_checkout(&self, request: CardCheckoutRequest) -> Result<CardCheckoutResponse> {
-        self.client.post("/version1/payments/card/checkout/request", &request).await
+
+    /// Card checkout, charging a card or a previously issued checkout token
+    pub async fn card_checkout(
+        &self,
+        request: CardCheckoutRequest,
+    ) -> Result<CardCheckoutResponse> {
+        self.client
+            .post_form("/version1/payments/card/checkout/request", &request)
+            .await
     }
-    
-    /// Validate card checkout
-    pub async fn validate_card_checkout(&self, request: ValidateCardCheckoutRequest) -> Result<ValidateCardCheckoutResponse> {
-        self.client.post("/version1/payments/card/checkout/validate", &request).await
+
+    /// Validate a pending card checkout with the OTP sent to the card holder
+    pub async fn validate_card_checkout(
+        &self,
+        request: ValidateCardCheckoutRequest,
+    ) -> Result<ValidateCardCheckoutResponse> {
+        self.client
+            .post_form("/version1/payments/card/checkout/validate", &request)
+            .await
     }
-    
+
     /// Find transaction
     pub async fn find_transaction(&self, transaction_id: &str) -> Result<FindTransactionResponse> {
-        let endpoint = format!("/version1/payments/find?transactionId={}", transaction_id);
+        let qs = serde_urlencoded::to_string([
+            ("username", self.client.config.username.as_str()),
+            ("transactionId", transaction_id),
+        ])
+        .map_err(|e| AfricasTalkingError::Internal(e.to_string()))?;
+        let endpoint = format!("/version1/payments/find?{}", qs);
         self.client.get(&endpoint).await
     }
-    
+
     /// Get wallet balance
     pub async fn get_wallet_balance(&self) -> Result<WalletBalanceResponse> {
-        self.client.get("/version1/payments/balance").await
+        let qs = serde_urlencoded::to_string([("username", self.client.config.username.as_str())])
+            .map_err(|e| AfricasTalkingError::Internal(e.to_string()))?;
+        let endpoint = format!("/version1/payments/balance?{}", qs);
+        self.client.get(&endpoint).await
     }
 
     /// Get wallet transactions
     pub async fn get_wallet_transactions(&self, request: WalletTransactionsRequest) -> Result<WalletTransactionsResponse> {
-        let mut query_params = Vec::new();
-        
+        let mut query_params = vec![("username", self.client.config.username.clone())];
+
         if let Some(page) = request.page {
             query_params.push(("page", page.to_string()));
         }
@@ -73,38 +126,420 @@ impl PaymentsModule {
         }
 
         let qs = serde_urlencoded::to_string(&query_params)
-            .map_err(AfricasTalkingError::Serialization)?;
+            .map_err(|e| AfricasTalkingError::Internal(e.to_string()))?;
         let endpoint = format!("/version1/payments/transactions?{}", qs);
         self.client.get(&endpoint).await
     }
+
+    /// Stream every wallet transaction, requesting pages from `request.page`
+    /// (or the first page) until `page * per_page` reaches `total`
+    #[cfg(feature = "stream")]
+    pub fn get_all_wallet_transactions(
+        &self,
+        request: WalletTransactionsRequest,
+    ) -> impl Stream<Item = Result<WalletTransaction>> + '_ {
+        let first_page = request.page.unwrap_or(1);
+        let state = (request, Some(first_page), VecDeque::new());
+
+        stream::unfold(
+            state,
+            move |(mut request, mut next_page, mut buffer)| async move {
+                loop {
+                    if let Some(transaction) = buffer.pop_front() {
+                        return Some((Ok(transaction), (request, next_page, buffer)));
+                    }
+                    let page = next_page?;
+
+                    request.page = Some(page);
+                    match self.get_wallet_transactions(request.clone()).await {
+                        Ok(response) => {
+                            next_page = if response.transactions.is_empty() {
+                                None
+                            } else {
+                                response.pagination.next_page()
+                            };
+                            buffer.extend(response.transactions);
+                        }
+                        Err(e) => return Some((Err(e), (request, None, buffer))),
+                    }
+                }
+            },
+        )
+    }
 }
 
 // --- Request and Response types for Payments Module ---
 
 #[derive(Debug, Serialize)]
 pub struct MobileCheckoutRequest {
+    #[serde(rename = "productName")]
     pub product_name: String,
-    pub provider: String,
+    #[serde(rename = "providerChannel", skip_serializing_if = "Option::is_none")]
+    pub provider_channel: Option<String>,
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+    #[serde(rename = "currencyCode")]
     pub currency_code: String,
     pub amount: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, String>>,
-    pub phone_number: String,
-    pub country_code: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct MobileCheckoutResponse {
-    pub provider: String,
     pub status: String,
-    pub request_id: String,
-    pub request_time: String,
-    pub receipt: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "transactionId")]
+    pub transaction_id: Option<String>,
+    #[serde(rename = "providerChannel")]
+    pub provider_channel: Option<String>,
     pub cost: Option<String>,
 }
 
-// (Other request/response structs like MobileB2BRequest, BankCheckoutRequest, etc. would follow here, as per the API specification.)
+impl MobileCheckoutResponse {
+    /// Parse the cost (e.g. `"KES 5.00"`) into its currency and amount, if
+    /// one was returned
+    ///
+    /// ```rust
+    /// use africastalking::{payments::MobileCheckoutResponse, Currency};
+    ///
+    /// let response: MobileCheckoutResponse = serde_json::from_str(
+    ///     r#"{"status": "PendingConfirmation", "cost": "KES 5.00"}"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(response.parsed_cost().unwrap().unwrap(), (Currency::Kes, 5.0));
+    ///
+    /// let response: MobileCheckoutResponse =
+    ///     serde_json::from_str(r#"{"status": "InvalidRequest"}"#).unwrap();
+    /// assert!(response.parsed_cost().is_none());
+    /// ```
+    pub fn parsed_cost(&self) -> Option<Result<(Currency, f64)>> {
+        self.cost.as_deref().map(parse_amount)
+    }
+}
 
 #[derive(Debug, Serialize)]
+pub struct MobileB2CRequest {
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    pub recipients: Vec<MobileB2CRecipient>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MobileB2CRecipient {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+    #[serde(rename = "currencyCode")]
+    pub currency_code: String,
+    pub amount: String,
+    #[serde(rename = "providerChannel", skip_serializing_if = "Option::is_none")]
+    pub provider_channel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MobileB2CResponse {
+    #[serde(rename = "numQueued")]
+    pub num_queued: Option<u32>,
+    #[serde(rename = "totalValue")]
+    pub total_value: Option<String>,
+    #[serde(rename = "totalTransactionFee")]
+    pub total_transaction_fee: Option<String>,
+    #[serde(default)]
+    pub entries: Vec<MobileB2CEntry>,
+    #[serde(rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MobileB2CEntry {
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+    pub status: String,
+    pub provider: Option<String>,
+    #[serde(rename = "providerChannel")]
+    pub provider_channel: Option<String>,
+    pub value: Option<String>,
+    #[serde(rename = "transactionId")]
+    pub transaction_id: Option<String>,
+    #[serde(rename = "transactionFee")]
+    pub transaction_fee: Option<String>,
+    #[serde(rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+/// Type of a mobile B2B transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum B2BTransferType {
+    BusinessBuyGoods,
+    BusinessPayBill,
+    DisburseFundsToBusiness,
+    BusinessToBusinessTransfer,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MobileB2BRequest {
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    pub provider: String,
+    #[serde(rename = "transferType")]
+    pub transfer_type: B2BTransferType,
+    #[serde(rename = "currencyCode")]
+    pub currency_code: String,
+    pub amount: String,
+    #[serde(rename = "destinationChannel")]
+    pub destination_channel: String,
+    #[serde(rename = "destinationAccount")]
+    pub destination_account: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MobileB2BResponse {
+    pub status: String,
+    #[serde(rename = "transactionId")]
+    pub transaction_id: Option<String>,
+    #[serde(rename = "transactionFee")]
+    pub transaction_fee: Option<String>,
+    #[serde(rename = "providerChannel")]
+    pub provider_channel: Option<String>,
+    #[serde(rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+/// Bank account details used for bank checkout and transfers
+#[derive(Debug, Clone, Serialize)]
+pub struct BankAccount {
+    #[serde(rename = "accountName")]
+    pub account_name: String,
+    #[serde(rename = "accountNumber")]
+    pub account_number: String,
+    #[serde(rename = "bankCode")]
+    pub bank_code: u32,
+    #[serde(rename = "branchCode", skip_serializing_if = "Option::is_none")]
+    pub branch_code: Option<String>,
+    #[serde(rename = "dateOfBirth", skip_serializing_if = "Option::is_none")]
+    pub date_of_birth: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BankCheckoutRequest {
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    #[serde(rename = "bankAccount")]
+    pub bank_account: BankAccount,
+    #[serde(rename = "currencyCode")]
+    pub currency_code: String,
+    pub amount: String,
+    pub narration: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BankCheckoutResponse {
+    pub status: String,
+    pub description: Option<String>,
+    #[serde(rename = "transactionId")]
+    pub transaction_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BankTransferRequest {
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    pub recipients: Vec<BankTransferRecipient>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BankTransferRecipient {
+    #[serde(rename = "bankAccount")]
+    pub bank_account: BankAccount,
+    #[serde(rename = "currencyCode")]
+    pub currency_code: String,
+    pub amount: String,
+    pub narration: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BankTransferResponse {
+    #[serde(default)]
+    pub entries: Vec<BankTransferEntry>,
+    #[serde(rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BankTransferEntry {
+    #[serde(rename = "accountNumber")]
+    pub account_number: String,
+    pub status: String,
+    #[serde(rename = "transactionId")]
+    pub transaction_id: Option<String>,
+    #[serde(rename = "transactionFee")]
+    pub transaction_fee: Option<String>,
+    #[serde(rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+/// Card details for a card checkout
+///
+/// The `Debug` output redacts the card number, CVV and auth token.
+#[derive(Clone, Serialize)]
+pub struct PaymentCard {
+    pub number: String,
+    #[serde(rename = "cvvNumber")]
+    pub cvv_number: String,
+    #[serde(rename = "expiryMonth")]
+    pub expiry_month: u32,
+    #[serde(rename = "expiryYear")]
+    pub expiry_year: u32,
+    /// ISO country code of the issuing bank, e.g. `"NG"`
+    #[serde(rename = "countryCode")]
+    pub country_code: String,
+    /// The card's PIN
+    #[serde(rename = "authToken")]
+    pub auth_token: String,
+}
+
+impl fmt::Debug for PaymentCard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PaymentCard")
+            .field("number", &"***redacted***")
+            .field("cvv_number", &"***redacted***")
+            .field("expiry_month", &self.expiry_month)
+            .field("expiry_year", &self.expiry_year)
+            .field("country_code", &self.country_code)
+            .field("auth_token", &"***redacted***")
+            .finish()
+    }
+}
+
+/// Card checkout request; set either `payment_card` or `checkout_token`
+#[derive(Debug, Serialize)]
+pub struct CardCheckoutRequest {
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    #[serde(rename = "paymentCard", skip_serializing_if = "Option::is_none")]
+    pub payment_card: Option<PaymentCard>,
+    #[serde(rename = "checkoutToken", skip_serializing_if = "Option::is_none")]
+    pub checkout_token: Option<String>,
+    #[serde(rename = "currencyCode")]
+    pub currency_code: String,
+    pub amount: String,
+    pub narration: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CardCheckoutResponse {
+    /// `PendingValidation` until the OTP is validated
+    pub status: CardCheckoutStatus,
+    pub description: Option<String>,
+    #[serde(rename = "transactionId")]
+    pub transaction_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidateCardCheckoutRequest {
+    #[serde(rename = "transactionId")]
+    pub transaction_id: String,
+    pub otp: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidateCardCheckoutResponse {
+    pub status: CardCheckoutStatus,
+    pub description: Option<String>,
+    /// Token that can be charged later instead of the card details
+    #[serde(rename = "checkoutToken")]
+    pub checkout_token: Option<String>,
+}
+
+/// Status of a card checkout
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "String")]
+#[non_exhaustive]
+pub enum CardCheckoutStatus {
+    /// Waiting for the OTP to be validated
+    PendingValidation,
+    Success,
+    Failed,
+    InvalidRequest,
+    NotSupported,
+    /// A status not known to this version of the SDK
+    Unknown(String),
+}
+
+impl From<String> for CardCheckoutStatus {
+    fn from(status: String) -> Self {
+        match status.as_str() {
+            "PendingValidation" => CardCheckoutStatus::PendingValidation,
+            "Success" => CardCheckoutStatus::Success,
+            "Failed" => CardCheckoutStatus::Failed,
+            "InvalidRequest" => CardCheckoutStatus::InvalidRequest,
+            "NotSupported" => CardCheckoutStatus::NotSupported,
+            _ => CardCheckoutStatus::Unknown(status),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FindTransactionResponse {
+    pub status: String,
+    pub data: Option<PaymentTransaction>,
+    #[serde(rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+/// A payment transaction as returned by the find and wallet queries
+#[derive(Debug, Deserialize)]
+pub struct PaymentTransaction {
+    #[serde(rename = "transactionId")]
+    pub transaction_id: String,
+    pub status: String,
+    pub category: Option<String>,
+    pub provider: Option<String>,
+    #[serde(rename = "providerChannel")]
+    pub provider_channel: Option<String>,
+    #[serde(rename = "productName")]
+    pub product_name: Option<String>,
+    #[serde(rename = "sourceType")]
+    pub source_type: Option<String>,
+    pub source: Option<String>,
+    #[serde(rename = "destinationType")]
+    pub destination_type: Option<String>,
+    pub destination: Option<String>,
+    pub value: Option<String>,
+    #[serde(rename = "transactionFee")]
+    pub transaction_fee: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "requestMetadata", default)]
+    pub request_metadata: HashMap<String, String>,
+    #[serde(rename = "transactionDate")]
+    pub transaction_date: Option<String>,
+    #[serde(rename = "creationTime")]
+    pub creation_time: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletBalanceResponse {
+    pub status: String,
+    pub balance: Option<String>,
+    #[serde(rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct WalletTransactionsRequest {
     pub page: Option<u32>,
     pub per_page: Option<u32>,
@@ -112,19 +547,590 @@ pub struct WalletTransactionsRequest {
     pub end_date: Option<String>,
 }
 
+/// A page of wallet transactions
+///
+/// ```rust
+/// use africastalking::payments::WalletTransactionsResponse;
+///
+/// let response: WalletTransactionsResponse = serde_json::from_str(
+///     r#"{"transactions": [], "total": 45, "page": 2, "perPage": 20}"#,
+/// )
+/// .unwrap();
+/// assert_eq!(response.pagination.per_page, 20);
+/// assert_eq!(response.pagination.next_page(), Some(3));
+/// ```
 #[derive(Debug, Deserialize)]
 pub struct WalletTransactionsResponse {
     pub transactions: Vec<WalletTransaction>,
-    pub total: u32,
-    pub page: u32,
-    pub per_page: u32,
+    #[serde(flatten)]
+    pub pagination: Pagination,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct WalletTransaction {
+    #[serde(rename = "transactionId")]
     pub transaction_id: String,
     pub amount: String,
     pub status: String,
     pub date: String,
     pub currency: String,
 }
+
+/// Final result of a payment, posted to the payment notification callback URL
+#[derive(Debug, Deserialize)]
+pub struct PaymentNotification {
+    #[serde(rename = "transactionId")]
+    pub transaction_id: String,
+    pub category: String,
+    pub provider: String,
+    #[serde(rename = "providerRefId")]
+    pub provider_ref_id: Option<String>,
+    #[serde(rename = "providerChannel")]
+    pub provider_channel: Option<String>,
+    #[serde(rename = "clientAccount")]
+    pub client_account: Option<String>,
+    #[serde(rename = "productName")]
+    pub product_name: Option<String>,
+    #[serde(rename = "sourceType")]
+    pub source_type: Option<String>,
+    pub source: Option<String>,
+    #[serde(rename = "destinationType")]
+    pub destination_type: Option<String>,
+    pub destination: Option<String>,
+    /// Amount including the currency, e.g. `"KES 100.0000"`
+    pub value: String,
+    #[serde(rename = "transactionFee")]
+    pub transaction_fee: Option<String>,
+    #[serde(rename = "providerFee")]
+    pub provider_fee: Option<String>,
+    pub status: PaymentStatus,
+    pub description: Option<String>,
+    #[serde(rename = "requestMetadata", default)]
+    pub request_metadata: HashMap<String, String>,
+    #[serde(rename = "providerMetadata", default)]
+    pub provider_metadata: HashMap<String, String>,
+    #[serde(rename = "transactionDate")]
+    pub transaction_date: Option<String>,
+}
+
+/// Status of a payment transaction
+///
+/// Statuses added by AfricasTalking later land in `Unknown`:
+///
+/// ```rust
+/// use africastalking::payments::PaymentStatus;
+///
+/// let status: PaymentStatus = serde_json::from_str(r#""Reversed""#).unwrap();
+/// assert_eq!(status, PaymentStatus::Unknown("Reversed".to_string()));
+/// assert_eq!(serde_json::to_string(&status).unwrap(), r#""Reversed""#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+#[non_exhaustive]
+pub enum PaymentStatus {
+    Success,
+    Failed,
+    Pending,
+    Queued,
+    /// A status not known to this version of the SDK
+    Unknown(String),
+}
+
+impl PaymentStatus {
+    /// The status as sent by AfricasTalking
+    pub fn as_str(&self) -> &str {
+        match self {
+            PaymentStatus::Success => "Success",
+            PaymentStatus::Failed => "Failed",
+            PaymentStatus::Pending => "Pending",
+            PaymentStatus::Queued => "Queued",
+            PaymentStatus::Unknown(status) => status,
+        }
+    }
+}
+
+impl From<String> for PaymentStatus {
+    fn from(status: String) -> Self {
+        match status.as_str() {
+            "Success" => PaymentStatus::Success,
+            "Failed" => PaymentStatus::Failed,
+            "Pending" => PaymentStatus::Pending,
+            "Queued" => PaymentStatus::Queued,
+            _ => PaymentStatus::Unknown(status),
+        }
+    }
+}
+
+impl From<PaymentStatus> for String {
+    fn from(status: PaymentStatus) -> Self {
+        match status {
+            PaymentStatus::Unknown(status) => status,
+            other => other.as_str().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockAfricasTalking;
+    use serde_json::json;
+
+    fn form_fields(body: &[u8]) -> HashMap<String, String> {
+        serde_urlencoded::from_bytes(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn mobile_b2b_reads_the_queued_transfer() {
+        let mock = MockAfricasTalking::start().await;
+        mock.mock_json(
+            "POST",
+            "/version1/payments/mobile/b2b/request",
+            201,
+            json!({
+                "status": "Queued",
+                "transactionId": "ATPid_SampleTxnId1",
+                "transactionFee": "KES 0.50",
+                "providerChannel": "525900"
+            }),
+        )
+        .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let response = client
+            .payments()
+            .mobile_b2b(MobileB2BRequest {
+                product_name: "My Product".into(),
+                provider: "Mpesa".into(),
+                transfer_type: B2BTransferType::BusinessPayBill,
+                currency_code: "KES".into(),
+                amount: "100.50".into(),
+                destination_channel: "525900".into(),
+                destination_account: "MyAccount".into(),
+                metadata: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.status, "Queued");
+        assert_eq!(
+            response.transaction_id.as_deref(),
+            Some("ATPid_SampleTxnId1")
+        );
+        assert_eq!(response.transaction_fee.as_deref(), Some("KES 0.50"));
+        assert_eq!(response.provider_channel.as_deref(), Some("525900"));
+        assert!(response.error_message.is_none());
+
+        let requests = mock.server().received_requests().await.unwrap();
+        let fields = form_fields(&requests[0].body);
+        assert_eq!(fields["username"], "sandbox");
+        assert_eq!(fields["transferType"], "BusinessPayBill");
+        assert_eq!(fields["destinationAccount"], "MyAccount");
+        assert_eq!(fields["amount"], "100.50");
+    }
+
+    #[test]
+    fn mobile_checkout_request_skips_unset_fields() {
+        let mut request = MobileCheckoutRequest {
+            product_name: "My Product".into(),
+            provider_channel: None,
+            phone_number: "+254711082000".into(),
+            currency_code: "KES".into(),
+            amount: "250.00".into(),
+            metadata: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "productName": "My Product",
+                "phoneNumber": "+254711082000",
+                "currencyCode": "KES",
+                "amount": "250.00"
+            })
+        );
+
+        request.provider_channel = Some("525900".into());
+        request.metadata = Some(HashMap::from([("orderId".into(), "42".into())]));
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["providerChannel"], "525900");
+        assert_eq!(json["metadata"], json!({ "orderId": "42" }));
+    }
+
+    #[test]
+    fn mobile_checkout_response_reads_a_pending_checkout() {
+        let response: MobileCheckoutResponse = serde_json::from_value(json!({
+            "status": "PendingConfirmation",
+            "description": "Waiting for user input",
+            "transactionId": "ATPid_SampleTxnId123",
+            "providerChannel": "525900"
+        }))
+        .unwrap();
+        assert_eq!(response.status, "PendingConfirmation");
+        assert_eq!(
+            response.description.as_deref(),
+            Some("Waiting for user input")
+        );
+        assert_eq!(
+            response.transaction_id.as_deref(),
+            Some("ATPid_SampleTxnId123")
+        );
+        assert_eq!(response.provider_channel.as_deref(), Some("525900"));
+        assert!(response.parsed_cost().is_none());
+    }
+
+    #[tokio::test]
+    async fn get_wallet_balance_encodes_the_username() {
+        use wiremock::{
+            Mock, ResponseTemplate,
+            matchers::{method, path, query_param},
+        };
+
+        let mock = MockAfricasTalking::start().await;
+        Mock::given(method("GET"))
+            .and(path("/version1/payments/balance"))
+            .and(query_param("username", "my app&co"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "status": "Success", "balance": "KES 1000.00" })),
+            )
+            .expect(1)
+            .mount(mock.server())
+            .await;
+        let config = crate::Config::new("test-api-key", "my app&co")
+            .base_url(mock.server().uri())
+            .max_retries(0);
+        let client = AfricasTalkingClient::new(config).unwrap();
+
+        let response = client.payments().get_wallet_balance().await.unwrap();
+        assert_eq!(response.balance.as_deref(), Some("KES 1000.00"));
+    }
+
+    fn bank_account(number: &str) -> BankAccount {
+        BankAccount {
+            account_name: "Test Account".into(),
+            account_number: number.into(),
+            bank_code: 234001,
+            branch_code: None,
+            date_of_birth: None,
+        }
+    }
+
+    #[test]
+    fn bank_transfer_request_serializes_each_recipient() {
+        let request = BankTransferRequest {
+            product_name: "My Product".into(),
+            recipients: vec![
+                BankTransferRecipient {
+                    bank_account: bank_account("1234567890"),
+                    currency_code: "NGN".into(),
+                    amount: "1000.50".into(),
+                    narration: "May salary".into(),
+                    metadata: None,
+                },
+                BankTransferRecipient {
+                    bank_account: BankAccount {
+                        branch_code: Some("001".into()),
+                        ..bank_account("0987654321")
+                    },
+                    currency_code: "NGN".into(),
+                    amount: "250.00".into(),
+                    narration: "Refund".into(),
+                    metadata: Some(HashMap::from([("ref".into(), "R-1".into())])),
+                },
+            ],
+        };
+
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "productName": "My Product",
+                "recipients": [
+                    {
+                        "bankAccount": {
+                            "accountName": "Test Account",
+                            "accountNumber": "1234567890",
+                            "bankCode": 234001
+                        },
+                        "currencyCode": "NGN",
+                        "amount": "1000.50",
+                        "narration": "May salary"
+                    },
+                    {
+                        "bankAccount": {
+                            "accountName": "Test Account",
+                            "accountNumber": "0987654321",
+                            "bankCode": 234001,
+                            "branchCode": "001"
+                        },
+                        "currencyCode": "NGN",
+                        "amount": "250.00",
+                        "narration": "Refund",
+                        "metadata": { "ref": "R-1" }
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn bank_transfer_response_reads_mixed_entries() {
+        let response: BankTransferResponse = serde_json::from_value(json!({
+            "entries": [
+                {
+                    "accountNumber": "1234567890",
+                    "status": "Queued",
+                    "transactionId": "ATPid_1",
+                    "transactionFee": "NGN 50.00"
+                },
+                {
+                    "accountNumber": "0987654321",
+                    "status": "InvalidRequest",
+                    "errorMessage": "Invalid bank code"
+                }
+            ]
+        }))
+        .unwrap();
+        assert!(response.error_message.is_none());
+
+        let [queued, failed] = &response.entries[..] else {
+            panic!("expected two entries, got {:?}", response.entries);
+        };
+        assert_eq!(queued.status, "Queued");
+        assert_eq!(queued.transaction_id.as_deref(), Some("ATPid_1"));
+        assert_eq!(queued.transaction_fee.as_deref(), Some("NGN 50.00"));
+        assert!(queued.error_message.is_none());
+        assert_eq!(failed.account_number, "0987654321");
+        assert_eq!(failed.status, "InvalidRequest");
+        assert!(failed.transaction_id.is_none());
+        assert_eq!(failed.error_message.as_deref(), Some("Invalid bank code"));
+    }
+
+    fn card_checkout(
+        payment_card: Option<PaymentCard>,
+        token: Option<&str>,
+    ) -> CardCheckoutRequest {
+        CardCheckoutRequest {
+            product_name: "My Product".into(),
+            payment_card,
+            checkout_token: token.map(Into::into),
+            currency_code: "NGN".into(),
+            amount: "500.00".into(),
+            narration: "Order 42".into(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn card_checkout_request_serializes_card_or_token() {
+        let card = PaymentCard {
+            number: "4223372036854775807".into(),
+            cvv_number: "123".into(),
+            expiry_month: 9,
+            expiry_year: 2030,
+            country_code: "NG".into(),
+            auth_token: "1234".into(),
+        };
+        assert_eq!(
+            serde_json::to_value(card_checkout(Some(card), None)).unwrap(),
+            json!({
+                "productName": "My Product",
+                "paymentCard": {
+                    "number": "4223372036854775807",
+                    "cvvNumber": "123",
+                    "expiryMonth": 9,
+                    "expiryYear": 2030,
+                    "countryCode": "NG",
+                    "authToken": "1234"
+                },
+                "currencyCode": "NGN",
+                "amount": "500.00",
+                "narration": "Order 42"
+            })
+        );
+
+        let json = serde_json::to_value(card_checkout(None, Some("ATCdTkn_1"))).unwrap();
+        assert_eq!(json["checkoutToken"], "ATCdTkn_1");
+        assert!(json.get("paymentCard").is_none());
+    }
+
+    #[tokio::test]
+    async fn card_checkout_moves_from_pending_validation_to_success() {
+        let mock = MockAfricasTalking::start().await;
+        mock.mock_json(
+            "POST",
+            "/version1/payments/card/checkout/request",
+            201,
+            json!({
+                "status": "PendingValidation",
+                "description": "Waiting for user input",
+                "transactionId": "ATPid_3"
+            }),
+        )
+        .await;
+        mock.mock_json(
+            "POST",
+            "/version1/payments/card/checkout/validate",
+            201,
+            json!({
+                "status": "Success",
+                "description": "Transaction completed",
+                "checkoutToken": "ATCdTkn_1"
+            }),
+        )
+        .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+        let payments = client.payments();
+
+        let checkout = payments
+            .card_checkout(card_checkout(None, Some("ATCdTkn_0")))
+            .await
+            .unwrap();
+        assert_eq!(checkout.status, CardCheckoutStatus::PendingValidation);
+        let transaction_id = checkout.transaction_id.unwrap();
+
+        let validated = payments
+            .validate_card_checkout(ValidateCardCheckoutRequest {
+                transaction_id,
+                otp: "1234".into(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(validated.status, CardCheckoutStatus::Success);
+        assert_eq!(validated.checkout_token.as_deref(), Some("ATCdTkn_1"));
+
+        let requests = mock.server().received_requests().await.unwrap();
+        let fields = form_fields(&requests[1].body);
+        assert_eq!(fields["transactionId"], "ATPid_3");
+        assert_eq!(fields["otp"], "1234");
+    }
+
+    #[test]
+    fn payment_notification_reads_a_mobile_checkout_callback() {
+        // Sample mobile checkout notification from the AfricasTalking payments docs
+        let notification: PaymentNotification = serde_json::from_str(
+            r#"{
+                "transactionId": "ATPid_SampleTxnId123",
+                "category": "MobileCheckout",
+                "provider": "Mpesa",
+                "providerRefId": "SAMPLE_MPESA_CODE",
+                "providerChannel": "525900",
+                "clientAccount": "MyAccount",
+                "productName": "My Online Store",
+                "sourceType": "PhoneNumber",
+                "source": "+254711082000",
+                "destinationType": "Wallet",
+                "destination": "PaymentWallet",
+                "value": "KES 100",
+                "transactionFee": "KES 1.00",
+                "providerFee": "KES 10.00",
+                "status": "Success",
+                "description": "The service request is processed successfully.",
+                "requestMetadata": { "item": "Vitamins", "shopId": "1234" },
+                "providerMetadata": { "KYCInfo1": "Value1", "KYCInfo2": "Value2" },
+                "transactionDate": "2018-07-22T20:41:02.312Z"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(notification.transaction_id, "ATPid_SampleTxnId123");
+        assert_eq!(notification.category, "MobileCheckout");
+        assert_eq!(notification.provider, "Mpesa");
+        assert_eq!(
+            notification.provider_ref_id.as_deref(),
+            Some("SAMPLE_MPESA_CODE")
+        );
+        assert_eq!(notification.source.as_deref(), Some("+254711082000"));
+        assert_eq!(notification.value, "KES 100");
+        assert_eq!(notification.provider_fee.as_deref(), Some("KES 10.00"));
+        assert_eq!(notification.status, PaymentStatus::Success);
+        assert_eq!(notification.request_metadata["shopId"], "1234");
+        assert_eq!(notification.provider_metadata["KYCInfo1"], "Value1");
+        assert_eq!(
+            notification.transaction_date.as_deref(),
+            Some("2018-07-22T20:41:02.312Z")
+        );
+    }
+
+    #[cfg(feature = "stream")]
+    mod stream {
+        use super::*;
+        use futures_util::StreamExt;
+        use wiremock::{
+            Mock, ResponseTemplate,
+            matchers::{method, path, query_param},
+        };
+
+        async fn mount_page(mock: &MockAfricasTalking, page: u32, ids: &[&str], total: u32) {
+            let transactions: Vec<_> = ids
+                .iter()
+                .map(|id| {
+                    json!({
+                        "transactionId": id,
+                        "amount": "100.00",
+                        "status": "Success",
+                        "date": "2024-01-01 10:00:00",
+                        "currency": "KES"
+                    })
+                })
+                .collect();
+            Mock::given(method("GET"))
+                .and(path("/version1/payments/transactions"))
+                .and(query_param("username", "sandbox"))
+                .and(query_param("page", page.to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "transactions": transactions,
+                    "page": page,
+                    "perPage": 2,
+                    "total": total
+                })))
+                .expect(1)
+                .mount(mock.server())
+                .await;
+        }
+
+        fn request() -> WalletTransactionsRequest {
+            WalletTransactionsRequest {
+                page: None,
+                per_page: Some(2),
+                start_date: None,
+                end_date: None,
+            }
+        }
+
+        async fn collect_ids(mock: &MockAfricasTalking) -> Vec<String> {
+            let client = AfricasTalkingClient::new(mock.config()).unwrap();
+            let payments = client.payments();
+            payments
+                .get_all_wallet_transactions(request())
+                .map(|transaction| transaction.unwrap().transaction_id)
+                .collect()
+                .await
+        }
+
+        #[tokio::test]
+        async fn stops_once_page_times_per_page_reaches_total() {
+            let mock = MockAfricasTalking::start().await;
+            mount_page(&mock, 1, &["ATPid_1", "ATPid_2"], 5).await;
+            mount_page(&mock, 2, &["ATPid_3", "ATPid_4"], 5).await;
+            mount_page(&mock, 3, &["ATPid_5"], 5).await;
+
+            let ids = collect_ids(&mock).await;
+            assert_eq!(ids, ["ATPid_1", "ATPid_2", "ATPid_3", "ATPid_4", "ATPid_5"]);
+            assert_eq!(mock.server().received_requests().await.unwrap().len(), 3);
+            mock.server().verify().await;
+        }
+
+        #[tokio::test]
+        async fn stops_at_an_empty_page() {
+            let mock = MockAfricasTalking::start().await;
+            // `total` overstates the transactions, so only the empty page ends the stream
+            mount_page(&mock, 1, &["ATPid_1", "ATPid_2"], 10).await;
+            mount_page(&mock, 2, &["ATPid_3", "ATPid_4"], 10).await;
+            mount_page(&mock, 3, &[], 10).await;
+
+            let ids = collect_ids(&mock).await;
+            assert_eq!(ids, ["ATPid_1", "ATPid_2", "ATPid_3", "ATPid_4"]);
+            assert_eq!(mock.server().received_requests().await.unwrap().len(), 3);
+            mock.server().verify().await;
+        }
+    }
+}
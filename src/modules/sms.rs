@@ -1,9 +1,34 @@
 //! SMS module implementation
 
-use crate::{client::AfricasTalkingClient, error::Result};
-use serde::{Deserialize, Serialize};
+use crate::{
+    client::AfricasTalkingClient,
+    error::{AfricasTalkingError, Result},
+    types::normalize_phone_numbers,
+};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+
+#[cfg(feature = "stream")]
+use futures_util::stream::{self, Stream};
+#[cfg(feature = "stream")]
+use std::collections::VecDeque;
 
 /// SMS module for sending and managing SMS messages
+///
+/// ```rust,no_run
+/// use africastalking::{AfricasTalkingClient, Config};
+///
+/// # #[tokio::main]
+/// # async fn main() -> africastalking::Result<()> {
+/// let client = AfricasTalkingClient::new(Config::from_env()?)?;
+/// let response = client
+///     .sms()
+///     .send_single("+254711000000", "Hello from Rust")
+///     .await?;
+/// println!("{}", response.sms_message_data.message);
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Debug, Clone)]
 pub struct SmsModule {
     client: AfricasTalkingClient,
@@ -16,8 +41,42 @@ impl SmsModule {
 
     /// Send SMS to one or more recipients
     pub async fn send(&self, request: SendSmsRequest) -> Result<SendSmsResponse> {
-        // let headers = self.get_sms_apis_headers();
-        self.client.post("/version1/messaging", &request).await
+        request.validate()?;
+        self.client.post_form("/version1/messaging", &request).await
+    }
+
+    /// Send a message to a single recipient
+    ///
+    /// Sends from the sender ID set with `Config::sender_id`, if any.
+    pub async fn send_single(&self, to: &str, message: &str) -> Result<SendSmsResponse> {
+        let mut request = SendSmsRequest::new(vec![to], message);
+        request.from = self.client.config.sms_short_code.clone();
+        self.send(request).await
+    }
+
+    /// Send the same message to many recipients via the JSON bulk endpoint
+    ///
+    /// Uses the sender ID set with `Config::sender_id`.
+    pub async fn send_bulk<S: Into<String>>(
+        &self,
+        phone_numbers: Vec<S>,
+        message: S,
+    ) -> Result<SendSmsResponse> {
+        let sender_id = self.client.config.sms_short_code.clone().ok_or_else(|| {
+            AfricasTalkingError::config(
+                "A sender ID is required for bulk SMS, see `Config::sender_id`",
+            )
+        })?;
+
+        let request = SendBulkSmsRequest {
+            username: self.client.config.username.clone(),
+            message: message.into(),
+            sender_id,
+            phone_numbers: phone_numbers.into_iter().map(Into::into).collect(),
+        };
+        self.client
+            .post_json("/version1/messaging/bulk", &request)
+            .await
     }
 
     /// Fetch SMS messages
@@ -31,22 +90,191 @@ impl SmsModule {
             "/version1/messaging".to_string()
         };
 
-        // let headers = self.get_sms_apis_headers();
         self.client.get(&endpoint).await
     }
+
+    /// Create a checkout token, needed to subscribe `phone_number` to a
+    /// premium keyword
+    pub async fn create_checkout_token(&self, phone_number: &str) -> Result<CheckoutTokenResponse> {
+        let request = CheckoutTokenRequest {
+            phone_number: phone_number.to_string(),
+        };
+        self.client
+            .post_form("/checkout/token/create", &request)
+            .await
+    }
+
+    /// Subscribe a user to a premium SMS keyword
+    ///
+    /// Needs a checkout token issued for the user's phone number.
+    pub async fn create_subscription(
+        &self,
+        request: CreateSubscriptionRequest,
+    ) -> Result<SubscriptionResponse> {
+        self.client
+            .post_form("/version1/subscription/create", &request)
+            .await
+    }
+
+    /// Unsubscribe a user from a premium SMS keyword
+    pub async fn delete_subscription(
+        &self,
+        request: DeleteSubscriptionRequest,
+    ) -> Result<SubscriptionResponse> {
+        self.client
+            .post_form("/version1/subscription/delete", &request)
+            .await
+    }
+
+    /// Fetch subscribers to a premium keyword, starting after
+    /// `last_received_id`
+    pub async fn fetch_subscriptions(
+        &self,
+        short_code: &str,
+        keyword: &str,
+        last_received_id: Option<u64>,
+    ) -> Result<FetchSubscriptionsResponse> {
+        let last_received_id = last_received_id.unwrap_or(0).to_string();
+        let qs = serde_urlencoded::to_string([
+            ("username", self.client.config.username.as_str()),
+            ("shortCode", short_code),
+            ("keyword", keyword),
+            ("lastReceivedId", last_received_id.as_str()),
+        ])
+        .map_err(|e| AfricasTalkingError::Internal(e.to_string()))?;
+
+        self.client
+            .get(&format!("/version1/subscription?{qs}"))
+            .await
+    }
+
+    /// Stream every inbox message, following `lastReceivedId` until an
+    /// empty page is returned
+    #[cfg(feature = "stream")]
+    pub fn fetch_all_messages(&self) -> impl Stream<Item = Result<SmsMessage>> + '_ {
+        let state = (None, VecDeque::new(), false);
+
+        stream::unfold(
+            state,
+            move |(mut last_received_id, mut buffer, mut done)| async move {
+                loop {
+                    if let Some(message) = buffer.pop_front() {
+                        return Some((Ok(message), (last_received_id, buffer, done)));
+                    }
+                    if done {
+                        return None;
+                    }
+
+                    match self.fetch_messages(last_received_id).await {
+                        Ok(response) => {
+                            let messages = response.sms_message_data.messages;
+                            match messages.last() {
+                                Some(last) => last_received_id = Some(last.id),
+                                None => return None,
+                            }
+                            buffer.extend(messages);
+                        }
+                        Err(e) => {
+                            done = true;
+                            return Some((Err(e), (last_received_id, buffer, done)));
+                        }
+                    }
+                }
+            },
+        )
+    }
 }
 
+/// Characters in the GSM 03.38 default alphabet
+const GSM7_BASIC: &str = "@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ !\"#¤%&'()*+,-./0123456789:;<=>?\
+    ¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà";
+
+/// Characters in the GSM 03.38 extension table, each taking two septets
+const GSM7_EXTENDED: &str = "^{}\\[~]|€\u{0C}";
+
+/// Character encoding an SMS will be sent with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmsEncoding {
+    /// GSM 03.38 7-bit alphabet
+    Gsm7,
+    /// UCS-2, used when the text has characters outside GSM-7
+    Ucs2,
+}
+
+/// How a message will be split into billable SMS parts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmsSegments {
+    pub encoding: SmsEncoding,
+    pub parts: u32,
+    pub chars_per_part: u32,
+}
+
+/// Work out the encoding and number of parts a message will be sent as
+///
+/// GSM-7 messages fit 160 characters in one part or 153 per part when
+/// concatenated; any other character forces UCS-2 at 70 or 67 per part.
+///
+/// ```rust
+/// use africastalking::sms::{segment_count, SmsEncoding};
+///
+/// assert_eq!(segment_count("Hello").parts, 1);
+/// assert_eq!(segment_count(&"a".repeat(161)).parts, 2);
+/// assert_eq!(segment_count("Hello 👋").encoding, SmsEncoding::Ucs2);
+/// ```
+pub fn segment_count(message: &str) -> SmsSegments {
+    let gsm7_len = message.chars().try_fold(0u32, |len, c| {
+        if GSM7_BASIC.contains(c) {
+            Some(len + 1)
+        } else if GSM7_EXTENDED.contains(c) {
+            Some(len + 2)
+        } else {
+            None
+        }
+    });
+
+    let (encoding, len, single, multi) = match gsm7_len {
+        Some(len) => (SmsEncoding::Gsm7, len, 160, 153),
+        None => (
+            SmsEncoding::Ucs2,
+            message.encode_utf16().count() as u32,
+            70,
+            67,
+        ),
+    };
+
+    if len <= single {
+        SmsSegments {
+            encoding,
+            parts: 1,
+            chars_per_part: single,
+        }
+    } else {
+        SmsSegments {
+            encoding,
+            parts: len.div_ceil(multi),
+            chars_per_part: multi,
+        }
+    }
+}
+
+/// Most recipients AfricasTalking accepts in a single send request
+pub const MAX_SMS_RECIPIENTS: usize = 1000;
+
 #[derive(Debug, Serialize)]
 pub struct SendSmsRequest {
+    /// Comma-separated recipients, the list format the form-encoded
+    /// `/version1/messaging` endpoint expects
     pub to: String,
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub from: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "bulkSMSMode")]
-    pub bulk_sms_mode: Option<u32>,
+    pub bulk_sms_mode: Option<BulkMode>,
+    /// Sent as `1` or `0`
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub enqueue: Option<u32>,
+    #[serde(serialize_with = "serialize_flag")]
+    pub enqueue: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub keyword: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -60,7 +288,11 @@ pub struct SendSmsRequest {
 impl SendSmsRequest {
     pub fn new<S: Into<String>>(to: Vec<S>, message: S) -> Self {
         Self {
-            to: to.into_iter().map(|s| s.into()).collect(),
+            to: to
+                .into_iter()
+                .map(Into::into)
+                .collect::<Vec<String>>()
+                .join(","),
             message: message.into(),
             from: None,
             bulk_sms_mode: None,
@@ -71,15 +303,185 @@ impl SendSmsRequest {
         }
     }
 
+    /// Create a request, normalizing recipients to E.164 first
+    ///
+    /// Numbers in local format get the dialing code of `default_country`,
+    /// e.g. `"KE"`. Malformed numbers are rejected with a validation error.
+    ///
+    /// ```rust
+    /// use africastalking::sms::SendSmsRequest;
+    ///
+    /// let request = SendSmsRequest::new_checked(vec!["0712345678"], "Hi", "KE").unwrap();
+    /// assert_eq!(request.to, "+254712345678");
+    /// assert!(SendSmsRequest::new_checked(vec!["07l2345678"], "Hi", "KE").is_err());
+    /// ```
+    pub fn new_checked<S: Into<String>>(
+        to: Vec<S>,
+        message: S,
+        default_country: &str,
+    ) -> Result<Self> {
+        let to = normalize_phone_numbers(to, default_country)?;
+        Ok(Self::new(to, message.into()))
+    }
+
+    /// Reply to an inbound message
+    ///
+    /// The reply goes back to the sender from the short code it was sent to,
+    /// echoing the `linkId` so premium replies are billed to the subscriber.
+    ///
+    /// ```rust
+    /// use africastalking::sms::{IncomingMessage, SendSmsRequest};
+    ///
+    /// let incoming = IncomingMessage {
+    ///     id: "1".into(),
+    ///     from: "+254711000000".into(),
+    ///     to: "22384".into(),
+    ///     text: "NEWS".into(),
+    ///     date: "2024-01-01 10:00:00".into(),
+    ///     link_id: Some("SampleLinkId123".into()),
+    ///     network_code: None,
+    /// };
+    ///
+    /// let reply = SendSmsRequest::reply_to(&incoming, "Subscribed");
+    /// assert_eq!(reply.to, "+254711000000");
+    /// assert_eq!(reply.from.as_deref(), Some("22384"));
+    /// assert_eq!(reply.link_id.as_deref(), Some("SampleLinkId123"));
+    /// ```
+    pub fn reply_to(incoming: &IncomingMessage, message: &str) -> Self {
+        let mut request =
+            Self::new(vec![incoming.from.as_str()], message).from(incoming.to.as_str());
+        request.link_id = incoming.reply_link_id().map(str::to_string);
+        request
+    }
+
     pub fn from<S: Into<String>>(mut self, from: S) -> Self {
         self.from = Some(from.into());
         self
     }
 
     pub fn bulk_mode(mut self, enabled: bool) -> Self {
-        self.bulk_sms_mode = Some(if enabled { 1 } else { 0 });
+        self.bulk_sms_mode = Some(BulkMode::from(enabled));
+        self
+    }
+
+    /// Queue the messages for delivery instead of sending them immediately
+    pub fn enqueue(mut self, enabled: bool) -> Self {
+        self.enqueue = Some(enabled);
+        self
+    }
+
+    /// How long delivery of premium messages is retried for
+    pub fn retry_duration_hours(mut self, hours: u32) -> Self {
+        self.retry_duration_in_hours = Some(hours);
         self
     }
+
+    /// Link ID of the incoming premium message being replied to
+    pub fn link_id<S: Into<String>>(mut self, link_id: S) -> Self {
+        self.link_id = Some(link_id.into());
+        self
+    }
+
+    /// Keyword of the premium subscription the message belongs to
+    pub fn keyword<S: Into<String>>(mut self, keyword: S) -> Self {
+        self.keyword = Some(keyword.into());
+        self
+    }
+
+    /// Check the request before sending it
+    ///
+    /// The message must not be blank and there must be between one and
+    /// [`MAX_SMS_RECIPIENTS`] recipients.
+    ///
+    /// ```rust
+    /// use africastalking::sms::{SendSmsRequest, MAX_SMS_RECIPIENTS};
+    ///
+    /// assert!(SendSmsRequest::new(vec!["+254711000000"], "Hi").validate().is_ok());
+    /// assert!(SendSmsRequest::new(vec!["+254711000000"], " ").validate().is_err());
+    ///
+    /// let recipients = vec!["+254711000000"; MAX_SMS_RECIPIENTS + 1];
+    /// assert!(SendSmsRequest::new(recipients, "Hi").validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        if self.message.trim().is_empty() {
+            return Err(AfricasTalkingError::validation(
+                "SMS message cannot be empty",
+            ));
+        }
+
+        let recipients = self
+            .to
+            .split(',')
+            .filter(|number| !number.trim().is_empty())
+            .count();
+        if recipients == 0 {
+            return Err(AfricasTalkingError::validation(
+                "SMS requests need at least one recipient",
+            ));
+        }
+        if recipients > MAX_SMS_RECIPIENTS {
+            return Err(AfricasTalkingError::validation(format!(
+                "SMS requests can have at most {MAX_SMS_RECIPIENTS} recipients, got {recipients}"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Value of the `bulkSMSMode` flag, sent as `1` or `0`
+///
+/// ```rust
+/// use africastalking::sms::BulkMode;
+///
+/// assert_eq!(serde_json::to_string(&BulkMode::Enabled).unwrap(), "1");
+/// assert_eq!(serde_json::to_string(&BulkMode::from(false)).unwrap(), "0");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkMode {
+    /// Bill the sender for the messages
+    Enabled,
+    Disabled,
+}
+
+impl From<bool> for BulkMode {
+    fn from(enabled: bool) -> Self {
+        if enabled {
+            BulkMode::Enabled
+        } else {
+            BulkMode::Disabled
+        }
+    }
+}
+
+impl Serialize for BulkMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_u8(match self {
+            BulkMode::Enabled => 1,
+            BulkMode::Disabled => 0,
+        })
+    }
+}
+
+/// Serialize an optional flag as the `1`/`0` integers AfricasTalking expects
+fn serialize_flag<S: Serializer>(
+    flag: &Option<bool>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match flag {
+        Some(flag) => serializer.serialize_u8(u8::from(*flag)),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SendBulkSmsRequest {
+    username: String,
+    message: String,
+    #[serde(rename = "senderId")]
+    sender_id: String,
+    #[serde(rename = "phoneNumbers")]
+    phone_numbers: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -92,24 +494,140 @@ pub struct SendSmsResponse {
 pub struct SmsMessageData {
     #[serde(rename = "Message")]
     pub message: String,
-    #[serde(rename = "Recipients")]
+    #[serde(rename = "Recipients", default)]
     pub recipients: Vec<SmsRecipient>,
 }
 
+/// Send result for one recipient
+///
+/// AfricasTalking leaves out some fields depending on the status, e.g. `cost`
+/// on queued messages, so those default to empty strings:
+///
+/// ```rust
+/// use africastalking::sms::SmsRecipient;
+///
+/// let recipient: SmsRecipient =
+///     serde_json::from_str(r#"{"statusCode": 102, "number": "+254711000000", "status": "Queued"}"#)
+///         .unwrap();
+/// assert_eq!(recipient.cost, "");
+/// assert!(recipient.parsed_status().is_success());
+/// ```
 #[derive(Debug, Deserialize)]
 pub struct SmsRecipient {
     #[serde(rename = "statusCode")]
     pub status_code: u32,
     #[serde(rename = "number")]
     pub number: String,
-    #[serde(rename = "status")]
+    #[serde(rename = "status", default)]
     pub status: String,
-    #[serde(rename = "cost")]
+    #[serde(rename = "cost", default)]
     pub cost: String,
-    #[serde(rename = "messageId")]
+    #[serde(rename = "messageId", default)]
     pub message_id: String,
 }
 
+impl SmsRecipient {
+    /// The recipient's `statusCode` as a typed status
+    pub fn parsed_status(&self) -> SmsDeliveryStatus {
+        SmsDeliveryStatus::from_code(self.status_code)
+    }
+}
+
+/// Per-recipient status codes returned when sending SMS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SmsDeliveryStatus {
+    Processed,
+    Sent,
+    Queued,
+    RiskHold,
+    InvalidSenderId,
+    InvalidPhoneNumber,
+    UnsupportedNumberType,
+    InsufficientBalance,
+    UserInBlacklist,
+    CouldNotRoute,
+    DoNotDisturbRejection,
+    InternalServerError,
+    GatewayError,
+    RejectedByGateway,
+    /// A code not known to this version of the SDK
+    Unknown(u32),
+}
+
+impl SmsDeliveryStatus {
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            100 => SmsDeliveryStatus::Processed,
+            101 => SmsDeliveryStatus::Sent,
+            102 => SmsDeliveryStatus::Queued,
+            401 => SmsDeliveryStatus::RiskHold,
+            402 => SmsDeliveryStatus::InvalidSenderId,
+            403 => SmsDeliveryStatus::InvalidPhoneNumber,
+            404 => SmsDeliveryStatus::UnsupportedNumberType,
+            405 => SmsDeliveryStatus::InsufficientBalance,
+            406 => SmsDeliveryStatus::UserInBlacklist,
+            407 => SmsDeliveryStatus::CouldNotRoute,
+            409 => SmsDeliveryStatus::DoNotDisturbRejection,
+            500 => SmsDeliveryStatus::InternalServerError,
+            501 => SmsDeliveryStatus::GatewayError,
+            502 => SmsDeliveryStatus::RejectedByGateway,
+            other => SmsDeliveryStatus::Unknown(other),
+        }
+    }
+
+    pub fn code(&self) -> u32 {
+        match self {
+            SmsDeliveryStatus::Processed => 100,
+            SmsDeliveryStatus::Sent => 101,
+            SmsDeliveryStatus::Queued => 102,
+            SmsDeliveryStatus::RiskHold => 401,
+            SmsDeliveryStatus::InvalidSenderId => 402,
+            SmsDeliveryStatus::InvalidPhoneNumber => 403,
+            SmsDeliveryStatus::UnsupportedNumberType => 404,
+            SmsDeliveryStatus::InsufficientBalance => 405,
+            SmsDeliveryStatus::UserInBlacklist => 406,
+            SmsDeliveryStatus::CouldNotRoute => 407,
+            SmsDeliveryStatus::DoNotDisturbRejection => 409,
+            SmsDeliveryStatus::InternalServerError => 500,
+            SmsDeliveryStatus::GatewayError => 501,
+            SmsDeliveryStatus::RejectedByGateway => 502,
+            SmsDeliveryStatus::Unknown(code) => *code,
+        }
+    }
+
+    /// Whether the message was accepted for delivery
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self,
+            SmsDeliveryStatus::Processed | SmsDeliveryStatus::Sent | SmsDeliveryStatus::Queued
+        )
+    }
+}
+
+impl fmt::Display for SmsDeliveryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status_str = match self {
+            SmsDeliveryStatus::Processed => "Processed",
+            SmsDeliveryStatus::Sent => "Sent",
+            SmsDeliveryStatus::Queued => "Queued",
+            SmsDeliveryStatus::RiskHold => "RiskHold",
+            SmsDeliveryStatus::InvalidSenderId => "InvalidSenderId",
+            SmsDeliveryStatus::InvalidPhoneNumber => "InvalidPhoneNumber",
+            SmsDeliveryStatus::UnsupportedNumberType => "UnsupportedNumberType",
+            SmsDeliveryStatus::InsufficientBalance => "InsufficientBalance",
+            SmsDeliveryStatus::UserInBlacklist => "UserInBlacklist",
+            SmsDeliveryStatus::CouldNotRoute => "CouldNotRoute",
+            SmsDeliveryStatus::DoNotDisturbRejection => "DoNotDisturbRejection",
+            SmsDeliveryStatus::InternalServerError => "InternalServerError",
+            SmsDeliveryStatus::GatewayError => "GatewayError",
+            SmsDeliveryStatus::RejectedByGateway => "RejectedByGateway",
+            SmsDeliveryStatus::Unknown(code) => return write!(f, "Unknown({code})"),
+        };
+        write!(f, "{}", status_str)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct FetchMessagesResponse {
     #[serde(rename = "SMSMessageData")]
@@ -118,7 +636,7 @@ pub struct FetchMessagesResponse {
 
 #[derive(Debug, Deserialize)]
 pub struct FetchSmsMessageData {
-    #[serde(rename = "Messages")]
+    #[serde(rename = "Messages", default)]
     pub messages: Vec<SmsMessage>,
 }
 
@@ -132,8 +650,528 @@ pub struct SmsMessage {
     pub from: String,
     #[serde(rename = "to")]
     pub to: String,
+    #[serde(rename = "date", default)]
+    pub date: String,
+    #[serde(rename = "linkId")]
+    pub link_id: Option<String>,
+}
+
+/// Delivery report AfricasTalking posts to the SMS delivery callback URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryReport {
+    /// ID of the message this report is for
+    pub id: String,
+    pub status: DeliveryStatus,
+    pub phone_number: String,
+    pub network_code: Option<String>,
+    /// Only present when the status is `Rejected` or `Failed`
+    pub failure_reason: Option<String>,
+    pub retry_count: Option<u32>,
+}
+
+/// Final delivery status of a message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+#[non_exhaustive]
+pub enum DeliveryStatus {
+    /// Delivered to the handset
+    Success,
+    /// Sent to the telco, awaiting confirmation
+    Sent,
+    /// Submitted to the telco
+    Submitted,
+    /// Queued by the telco, e.g. while the handset is off
+    Buffered,
+    /// Rejected by the telco
+    Rejected,
+    /// Could not be delivered
+    Failed,
+    /// A status not known to this version of the SDK
+    Unknown(String),
+}
+
+impl DeliveryStatus {
+    /// The status as sent by AfricasTalking
+    pub fn as_str(&self) -> &str {
+        match self {
+            DeliveryStatus::Success => "Success",
+            DeliveryStatus::Sent => "Sent",
+            DeliveryStatus::Submitted => "Submitted",
+            DeliveryStatus::Buffered => "Buffered",
+            DeliveryStatus::Rejected => "Rejected",
+            DeliveryStatus::Failed => "Failed",
+            DeliveryStatus::Unknown(status) => status,
+        }
+    }
+}
+
+impl From<String> for DeliveryStatus {
+    fn from(status: String) -> Self {
+        match status.as_str() {
+            "Success" => DeliveryStatus::Success,
+            "Sent" => DeliveryStatus::Sent,
+            "Submitted" => DeliveryStatus::Submitted,
+            "Buffered" => DeliveryStatus::Buffered,
+            "Rejected" => DeliveryStatus::Rejected,
+            "Failed" => DeliveryStatus::Failed,
+            _ => DeliveryStatus::Unknown(status),
+        }
+    }
+}
+
+impl From<DeliveryStatus> for String {
+    fn from(status: DeliveryStatus) -> Self {
+        match status {
+            DeliveryStatus::Unknown(status) => status,
+            other => other.as_str().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CheckoutTokenRequest {
+    #[serde(rename = "phoneNumber")]
+    phone_number: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckoutTokenResponse {
+    /// Empty when the token could not be created
+    #[serde(default)]
+    pub token: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateSubscriptionRequest {
+    #[serde(rename = "shortCode")]
+    pub short_code: String,
+    pub keyword: String,
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+    #[serde(rename = "checkoutToken")]
+    pub checkout_token: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteSubscriptionRequest {
+    #[serde(rename = "shortCode")]
+    pub short_code: String,
+    pub keyword: String,
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+}
+
+/// Result of creating or deleting a premium subscription
+#[derive(Debug, Deserialize)]
+pub struct SubscriptionResponse {
+    pub status: String,
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FetchSubscriptionsResponse {
+    #[serde(default)]
+    pub responses: Vec<Subscription>,
+}
+
+/// A subscriber to a premium keyword
+#[derive(Debug, Deserialize)]
+pub struct Subscription {
+    /// Pass the last `id` as `last_received_id` to fetch the next page
+    pub id: u64,
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+    pub date: String,
+}
+
+/// Inbound message AfricasTalking posts to the incoming SMS callback URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncomingMessage {
+    #[serde(rename = "id")]
+    pub id: String,
+    /// Sender's phone number
+    #[serde(rename = "from")]
+    pub from: String,
+    /// Short code or number the message was sent to
+    #[serde(rename = "to")]
+    pub to: String,
+    #[serde(rename = "text")]
+    pub text: String,
     #[serde(rename = "date")]
     pub date: String,
+    /// Set for premium messages and must be echoed on the reply
     #[serde(rename = "linkId")]
     pub link_id: Option<String>,
+    #[serde(rename = "networkCode")]
+    pub network_code: Option<String>,
+}
+
+impl IncomingMessage {
+    /// The `linkId` to send with a premium two-way reply, if any
+    pub fn reply_link_id(&self) -> Option<&str> {
+        self.link_id.as_deref().filter(|id| !id.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockAfricasTalking;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn form_fields(body: &[u8]) -> HashMap<String, String> {
+        serde_urlencoded::from_bytes(body).unwrap()
+    }
+
+    fn sent_response() -> serde_json::Value {
+        json!({ "SMSMessageData": { "Message": "Sent to 1/1", "Recipients": [] } })
+    }
+
+    #[test]
+    fn new_joins_recipients_with_commas() {
+        let request = SendSmsRequest::new(vec!["254711", "254722"], "Hello");
+        assert_eq!(request.to, "254711,254722");
+    }
+
+    #[test]
+    fn new_checked_normalizes_recipients_in_the_payload() {
+        let request =
+            SendSmsRequest::new_checked(vec!["0712345678", "+447700900123"], "Hello", "KE")
+                .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["to"], "+254712345678,+447700900123");
+    }
+
+    #[test]
+    fn new_checked_rejects_malformed_numbers() {
+        let error = SendSmsRequest::new_checked(vec!["07l2345678"], "Hello", "KE").unwrap_err();
+        assert!(matches!(error, AfricasTalkingError::Validation(_)));
+    }
+
+    #[test]
+    fn delivery_report_reads_a_failure_reason() {
+        let report: DeliveryReport = serde_json::from_str(
+            r#"{
+                "id": "ATXid_dda2a2b0a3ad1a6c3e2c1cd2ed0a8d4c",
+                "status": "Failed",
+                "phoneNumber": "+254711082000",
+                "networkCode": "63902",
+                "failureReason": "UserInBlacklist",
+                "retryCount": 0
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(report.status, DeliveryStatus::Failed);
+        assert_eq!(report.phone_number, "+254711082000");
+        assert_eq!(report.network_code.as_deref(), Some("63902"));
+        assert_eq!(report.failure_reason.as_deref(), Some("UserInBlacklist"));
+        assert_eq!(report.retry_count, Some(0));
+    }
+
+    #[test]
+    fn delivery_report_without_a_failure_reason() {
+        let report: DeliveryReport = serde_json::from_str(
+            r#"{
+                "id": "ATXid_dda2a2b0a3ad1a6c3e2c1cd2ed0a8d4c",
+                "status": "Success",
+                "phoneNumber": "+254711082000",
+                "networkCode": "63902",
+                "retryCount": 0
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(report.status, DeliveryStatus::Success);
+        assert!(report.failure_reason.is_none());
+    }
+
+    #[test]
+    fn incoming_message_reads_a_callback_payload() {
+        let message: IncomingMessage = serde_json::from_str(
+            r#"{
+                "date": "2024-03-14 09:41:22",
+                "from": "+254711082000",
+                "id": "5c8e3a2b-7f1d-4e0a-9b6c-2d4f8a1e3c5b",
+                "linkId": "SampleLinkId123",
+                "text": "NEWS",
+                "to": "22384",
+                "networkCode": "63902"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(message.from, "+254711082000");
+        assert_eq!(message.to, "22384");
+        assert_eq!(message.text, "NEWS");
+        assert_eq!(message.link_id.as_deref(), Some("SampleLinkId123"));
+        assert_eq!(message.reply_link_id(), Some("SampleLinkId123"));
+        assert_eq!(message.network_code.as_deref(), Some("63902"));
+    }
+
+    #[test]
+    fn from_code_maps_every_known_status_code() {
+        let cases = [
+            (100, SmsDeliveryStatus::Processed),
+            (101, SmsDeliveryStatus::Sent),
+            (102, SmsDeliveryStatus::Queued),
+            (401, SmsDeliveryStatus::RiskHold),
+            (402, SmsDeliveryStatus::InvalidSenderId),
+            (403, SmsDeliveryStatus::InvalidPhoneNumber),
+            (404, SmsDeliveryStatus::UnsupportedNumberType),
+            (405, SmsDeliveryStatus::InsufficientBalance),
+            (406, SmsDeliveryStatus::UserInBlacklist),
+            (407, SmsDeliveryStatus::CouldNotRoute),
+            (409, SmsDeliveryStatus::DoNotDisturbRejection),
+            (500, SmsDeliveryStatus::InternalServerError),
+            (501, SmsDeliveryStatus::GatewayError),
+            (502, SmsDeliveryStatus::RejectedByGateway),
+        ];
+        for (code, status) in cases {
+            assert_eq!(SmsDeliveryStatus::from_code(code), status, "{code}");
+            assert_eq!(status.code(), code);
+        }
+    }
+
+    #[test]
+    fn from_code_keeps_unknown_codes() {
+        let status = SmsDeliveryStatus::from_code(408);
+        assert_eq!(status, SmsDeliveryStatus::Unknown(408));
+        assert_eq!(status.code(), 408);
+        assert!(!status.is_success());
+    }
+
+    #[tokio::test]
+    async fn send_single_sends_from_the_configured_sender_id() {
+        let mock = MockAfricasTalking::start().await;
+        mock.send_sms(sent_response()).await;
+        let client = AfricasTalkingClient::new(mock.config().sender_id("ALERTS")).unwrap();
+
+        client
+            .sms()
+            .send_single("+254711082000", "Hello")
+            .await
+            .unwrap();
+
+        let requests = mock.server().received_requests().await.unwrap();
+        let fields = form_fields(&requests[0].body);
+        assert_eq!(fields["from"], "ALERTS");
+        assert_eq!(fields["to"], "+254711082000");
+    }
+
+    #[tokio::test]
+    async fn send_single_omits_from_without_a_sender_id() {
+        let mock = MockAfricasTalking::start().await;
+        mock.send_sms(sent_response()).await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        client
+            .sms()
+            .send_single("+254711082000", "Hello")
+            .await
+            .unwrap();
+
+        let requests = mock.server().received_requests().await.unwrap();
+        assert!(!form_fields(&requests[0].body).contains_key("from"));
+    }
+
+    #[test]
+    fn chained_setters_serialize_to_the_api_field_names() {
+        let request = SendSmsRequest::new(vec!["+254711082000"], "Hello")
+            .from("22384")
+            .bulk_mode(true)
+            .enqueue(true)
+            .keyword("NEWS")
+            .link_id("SampleLinkId123")
+            .retry_duration_hours(6);
+
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "to": "+254711082000",
+                "message": "Hello",
+                "from": "22384",
+                "bulkSMSMode": 1,
+                "enqueue": 1,
+                "keyword": "NEWS",
+                "linkId": "SampleLinkId123",
+                "retryDurationInHours": 6
+            })
+        );
+    }
+
+    #[test]
+    fn unset_options_are_left_out() {
+        let request = SendSmsRequest::new(vec!["+254711082000"], "Hello").enqueue(false);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({ "to": "+254711082000", "message": "Hello", "enqueue": 0 })
+        );
+    }
+
+    #[tokio::test]
+    async fn create_subscription_sends_the_checkout_token() {
+        let mock = MockAfricasTalking::start().await;
+        mock.mock_json(
+            "POST",
+            "/version1/subscription/create",
+            201,
+            json!({ "status": "Success", "description": "Waiting for user input" }),
+        )
+        .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let response = client
+            .sms()
+            .create_subscription(CreateSubscriptionRequest {
+                short_code: "22384".into(),
+                keyword: "NEWS".into(),
+                phone_number: "+254711082000".into(),
+                checkout_token: "CkTkn_SampleToken".into(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.status, "Success");
+
+        let requests = mock.server().received_requests().await.unwrap();
+        assert_eq!(
+            form_fields(&requests[0].body),
+            HashMap::from([
+                ("username".to_string(), "sandbox".to_string()),
+                ("shortCode".to_string(), "22384".to_string()),
+                ("keyword".to_string(), "NEWS".to_string()),
+                ("phoneNumber".to_string(), "+254711082000".to_string()),
+                ("checkoutToken".to_string(), "CkTkn_SampleToken".to_string()),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_subscription_sends_the_subscriber() {
+        let mock = MockAfricasTalking::start().await;
+        mock.mock_json(
+            "POST",
+            "/version1/subscription/delete",
+            201,
+            json!({ "status": "Success", "description": "Succeeded" }),
+        )
+        .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        client
+            .sms()
+            .delete_subscription(DeleteSubscriptionRequest {
+                short_code: "22384".into(),
+                keyword: "NEWS".into(),
+                phone_number: "+254711082000".into(),
+            })
+            .await
+            .unwrap();
+
+        let requests = mock.server().received_requests().await.unwrap();
+        assert_eq!(
+            form_fields(&requests[0].body),
+            HashMap::from([
+                ("username".to_string(), "sandbox".to_string()),
+                ("shortCode".to_string(), "22384".to_string()),
+                ("keyword".to_string(), "NEWS".to_string()),
+                ("phoneNumber".to_string(), "+254711082000".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn fetch_subscriptions_response_reads_each_subscriber() {
+        let response: FetchSubscriptionsResponse = serde_json::from_str(
+            r#"{
+                "responses": [
+                    { "id": 100, "phoneNumber": "+254711082000", "date": "2024-03-14 09:41:22" },
+                    { "id": 101, "phoneNumber": "+254722000000", "date": "2024-03-15 10:02:11" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(response.responses.len(), 2);
+        assert_eq!(response.responses[1].id, 101);
+        assert_eq!(response.responses[1].phone_number, "+254722000000");
+
+        let empty: FetchSubscriptionsResponse = serde_json::from_str("{}").unwrap();
+        assert!(empty.responses.is_empty());
+    }
+
+    #[test]
+    fn checkout_token_response_reads_the_token() {
+        let response: CheckoutTokenResponse = serde_json::from_str(
+            r#"{ "description": "Success", "token": "CkTkn_SampleCkTknId123" }"#,
+        )
+        .unwrap();
+        assert_eq!(response.token, "CkTkn_SampleCkTknId123");
+        assert_eq!(response.description, "Success");
+
+        let failed: CheckoutTokenResponse =
+            serde_json::from_str(r#"{ "description": "Failed" }"#).unwrap();
+        assert!(failed.token.is_empty());
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn fetch_all_messages_follows_last_received_id_until_an_empty_page() {
+        use futures_util::StreamExt;
+        use wiremock::{
+            Mock, ResponseTemplate,
+            matchers::{method, path, query_param, query_param_is_missing},
+        };
+
+        fn page(ids: &[u32]) -> ResponseTemplate {
+            let messages: Vec<_> = ids
+                .iter()
+                .map(|id| {
+                    json!({
+                        "id": id,
+                        "text": format!("message {id}"),
+                        "from": "+254711082000",
+                        "to": "12345",
+                        "date": "2024-01-01T00:00:00Z",
+                        "linkId": null
+                    })
+                })
+                .collect();
+            ResponseTemplate::new(200).set_body_json(json!({
+                "SMSMessageData": { "Messages": messages }
+            }))
+        }
+
+        let mock = MockAfricasTalking::start().await;
+        Mock::given(method("GET"))
+            .and(path("/version1/messaging"))
+            .and(query_param_is_missing("lastReceivedId"))
+            .respond_with(page(&[1, 2]))
+            .expect(1)
+            .mount(mock.server())
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/version1/messaging"))
+            .and(query_param("lastReceivedId", "2"))
+            .respond_with(page(&[3]))
+            .expect(1)
+            .mount(mock.server())
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/version1/messaging"))
+            .and(query_param("lastReceivedId", "3"))
+            .respond_with(page(&[]))
+            .expect(1)
+            .mount(mock.server())
+            .await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let sms = client.sms();
+        let ids: Vec<u32> = sms
+            .fetch_all_messages()
+            .map(|message| message.unwrap().id)
+            .collect()
+            .await;
+        assert_eq!(ids, [1, 2, 3]);
+        mock.server().verify().await;
+    }
 }
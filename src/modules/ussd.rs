@@ -0,0 +1,1381 @@
+//! USSD module implementation
+//!
+//! USSD sessions are driven by AfricasTalking calling your callback URL, so
+//! this module provides the callback payload types rather than API calls.
+
+use crate::{
+    error::{AfricasTalkingError, Result},
+    types::{Currency, dialing_code, parse_amount},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::HashMap, fmt, future::Future, marker::PhantomData, str::FromStr, sync::Arc,
+    time::Duration,
+};
+use tokio::{sync::RwLock, time::Instant};
+
+/// Payload AfricasTalking posts to the USSD callback URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UssdRequest {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "serviceCode")]
+    pub service_code: String,
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+    /// All inputs in the session so far, separated by `*`
+    pub text: String,
+    /// Empty when AfricasTalking leaves it out, as it does for some
+    /// aggregators
+    #[serde(rename = "networkCode", default)]
+    pub network_code: String,
+}
+
+impl UssdRequest {
+    /// The network the user is dialing from
+    ///
+    /// ```rust
+    /// use africastalking::ussd::{NetworkCode, UssdRequest};
+    ///
+    /// let request: UssdRequest = serde_json::from_str(
+    ///     r#"{"sessionId": "ATUid_1", "serviceCode": "*384#", "phoneNumber": "+254711000000", "text": ""}"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(request.network_code, "");
+    /// assert_eq!(request.network(), NetworkCode::Unknown(String::new()));
+    /// ```
+    pub fn network(&self) -> NetworkCode {
+        NetworkCode::from_code(&self.network_code)
+    }
+
+    /// The most recent input, or `""` at the start of the session
+    pub fn current_input(&self) -> &str {
+        self.text.rsplit('*').next().unwrap_or_default()
+    }
+
+    /// Parse the most recent input, e.g. as a number
+    pub fn current_input_as<T: FromStr>(&self) -> Option<T> {
+        self.current_input().trim().parse().ok()
+    }
+
+    /// Parse the most recent input as an amount between `min` and `max`
+    /// inclusive
+    ///
+    /// The error is a short message suitable for showing to the user.
+    pub fn current_input_amount(
+        &self,
+        min: f64,
+        max: f64,
+    ) -> std::result::Result<f64, &'static str> {
+        let amount: f64 = self
+            .current_input_as()
+            .filter(|amount: &f64| amount.is_finite())
+            .ok_or("Please enter a valid amount")?;
+
+        if amount < min {
+            Err("Amount is below the minimum")
+        } else if amount > max {
+            Err("Amount is above the maximum")
+        } else {
+            Ok(amount)
+        }
+    }
+
+    /// Every input in the session so far, oldest first
+    pub fn inputs(&self) -> Vec<&str> {
+        if self.text.is_empty() {
+            Vec::new()
+        } else {
+            self.text.split('*').collect()
+        }
+    }
+
+    /// The session text without its last input, e.g. `"1*2"` for `"1*2*3"`
+    ///
+    /// Returns `None` at the top level.
+    pub fn parent_path(&self) -> Option<&str> {
+        self.text.rsplit_once('*').map(|(parent, _)| parent)
+    }
+
+    /// The last `n` inputs joined with `*`, or the whole text if there are
+    /// fewer than `n`
+    pub fn last_n(&self, n: usize) -> &str {
+        if n == 0 {
+            return "";
+        }
+        match self.text.rmatch_indices('*').nth(n - 1) {
+            Some((index, _)) => &self.text[index + 1..],
+            None => &self.text,
+        }
+    }
+
+    /// The inputs following a menu `prefix`, e.g. `"3"` for `"1*2*3"` and
+    /// prefix `"1*2"`
+    ///
+    /// Returns `None` unless `prefix` matches whole inputs at the start of the
+    /// text. A text equal to the prefix yields `Some("")`.
+    ///
+    /// ```rust
+    /// # use africastalking::ussd::UssdRequest;
+    /// # let request = UssdRequest {
+    /// #     session_id: "ATUid_1".into(),
+    /// #     service_code: "*384#".into(),
+    /// #     phone_number: "+254711000000".into(),
+    /// #     text: "1*2*3".into(),
+    /// #     network_code: "63902".into(),
+    /// # };
+    /// assert_eq!(request.parent_path(), Some("1*2"));
+    /// assert_eq!(request.strip_prefix_path("1*2"), Some("3"));
+    /// assert_eq!(request.strip_prefix_path("1*22"), None);
+    /// ```
+    pub fn strip_prefix_path(&self, prefix: &str) -> Option<&str> {
+        if prefix.is_empty() {
+            return Some(&self.text);
+        }
+        let rest = self.text.strip_prefix(prefix)?;
+        if rest.is_empty() {
+            Some(rest)
+        } else {
+            rest.strip_prefix('*')
+        }
+    }
+}
+
+/// Session summary AfricasTalking posts to the USSD events callback URL once
+/// a session ends
+#[derive(Debug, Clone, Deserialize)]
+pub struct UssdNotification {
+    pub date: String,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "serviceCode")]
+    pub service_code: String,
+    #[serde(rename = "networkCode")]
+    pub network_code: String,
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+    pub status: UssdSessionStatus,
+    /// Session cost including the currency, e.g. `"KES 0.50"`
+    pub cost: String,
+    /// Session length in milliseconds, as a string
+    #[serde(rename = "durationInMillis")]
+    pub duration_in_millis: String,
+    #[serde(rename = "hopsCount")]
+    pub hops_count: Option<String>,
+    /// All inputs in the session, separated by `*`
+    pub input: Option<String>,
+    #[serde(rename = "lastAppResponse")]
+    pub last_app_response: Option<String>,
+    #[serde(rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+impl UssdNotification {
+    /// Parse the cost (e.g. `"KES 0.50"`) into its currency and amount
+    pub fn parsed_cost(&self) -> Result<(Currency, f64)> {
+        parse_amount(&self.cost)
+    }
+
+    /// Parse `durationInMillis` into a `Duration`
+    pub fn duration(&self) -> Result<Duration> {
+        self.duration_in_millis
+            .trim()
+            .parse()
+            .map(Duration::from_millis)
+            .map_err(|_| {
+                AfricasTalkingError::validation(format!(
+                    "Invalid session duration: {:?}",
+                    self.duration_in_millis
+                ))
+            })
+    }
+
+    /// The inputs the user entered, oldest first, like [`UssdRequest::inputs`]
+    ///
+    /// ```rust
+    /// use africastalking::ussd::UssdNotification;
+    ///
+    /// let notification: UssdNotification = serde_json::from_value(serde_json::json!({
+    ///     "date": "2024-01-01 10:00:00",
+    ///     "sessionId": "ATUid_1",
+    ///     "serviceCode": "*384#",
+    ///     "networkCode": "63902",
+    ///     "phoneNumber": "+254711000000",
+    ///     "status": "Success",
+    ///     "cost": "KES 0.50",
+    ///     "durationInMillis": "12000",
+    ///     "hopsCount": "4",
+    ///     "input": "1*2*4*1234",
+    /// }))
+    /// .unwrap();
+    ///
+    /// assert_eq!(notification.final_path(), ["1", "2", "4", "1234"]);
+    /// assert_eq!(notification.reached_depth(), 4);
+    /// ```
+    pub fn final_path(&self) -> Vec<&str> {
+        match self.input.as_deref() {
+            None | Some("") => Vec::new(),
+            Some(input) => input.split('*').collect(),
+        }
+    }
+
+    /// How many menu levels deep the user got
+    pub fn reached_depth(&self) -> usize {
+        self.final_path().len()
+    }
+}
+
+/// Final status of a USSD session
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "String")]
+#[non_exhaustive]
+pub enum UssdSessionStatus {
+    Success,
+    /// The user left before the session was ended with `END`
+    Incomplete,
+    Failed,
+    /// A status not known to this version of the SDK
+    Unknown(String),
+}
+
+impl From<String> for UssdSessionStatus {
+    fn from(status: String) -> Self {
+        match status.as_str() {
+            "Success" => UssdSessionStatus::Success,
+            "Incomplete" => UssdSessionStatus::Incomplete,
+            "Failed" => UssdSessionStatus::Failed,
+            _ => UssdSessionStatus::Unknown(status),
+        }
+    }
+}
+
+/// Response returned from a USSD callback
+///
+/// Renders as `CON <message>` to keep the session open or `END <message>` to
+/// close it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UssdResponse {
+    /// Show the message and wait for more input
+    Continue(String),
+    /// Show the message and end the session
+    End(String),
+}
+
+impl UssdResponse {
+    /// Respond and keep the session open
+    pub fn continues<S: Into<String>>(message: S) -> Self {
+        UssdResponse::Continue(message.into())
+    }
+
+    /// Respond and end the session
+    pub fn ends<S: Into<String>>(message: S) -> Self {
+        UssdResponse::End(message.into())
+    }
+
+    /// Respond and keep the session open, failing if the message is longer
+    /// than `max_bytes` (usually [`USSD_SCREEN_BYTES`])
+    ///
+    /// ```rust
+    /// use africastalking::ussd::{USSD_SCREEN_BYTES, UssdResponse};
+    ///
+    /// assert!(UssdResponse::try_continues("a".repeat(182), USSD_SCREEN_BYTES).is_ok());
+    /// assert!(UssdResponse::try_continues("a".repeat(183), USSD_SCREEN_BYTES).is_err());
+    /// ```
+    pub fn try_continues<S: Into<String>>(message: S, max_bytes: usize) -> Result<Self> {
+        Self::continues(message).within(max_bytes)
+    }
+
+    /// Respond and end the session, failing if the message is longer than
+    /// `max_bytes` (usually [`USSD_SCREEN_BYTES`])
+    pub fn try_ends<S: Into<String>>(message: S, max_bytes: usize) -> Result<Self> {
+        Self::ends(message).within(max_bytes)
+    }
+
+    fn within(self, max_bytes: usize) -> Result<Self> {
+        if self.byte_len() > max_bytes {
+            return Err(AfricasTalkingError::validation(format!(
+                "USSD message is {} bytes, more than the {max_bytes} byte limit",
+                self.byte_len()
+            )));
+        }
+        Ok(self)
+    }
+
+    /// Length of the message in bytes, excluding the `CON`/`END` prefix
+    pub fn byte_len(&self) -> usize {
+        self.message().len()
+    }
+
+    /// Whether this response ends the session
+    pub fn is_ending(&self) -> bool {
+        matches!(self, UssdResponse::End(_))
+    }
+
+    /// The message shown to the user
+    pub fn message(&self) -> &str {
+        match self {
+            UssdResponse::Continue(message) | UssdResponse::End(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for UssdResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UssdResponse::Continue(message) => write!(f, "CON {message}"),
+            UssdResponse::End(message) => write!(f, "END {message}"),
+        }
+    }
+}
+
+impl FromStr for UssdResponse {
+    type Err = AfricasTalkingError;
+
+    /// Parse a raw `CON ...`/`END ...` response string
+    fn from_str(s: &str) -> Result<Self> {
+        fn body<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+            let rest = s.strip_prefix(prefix)?;
+            if rest.is_empty() {
+                Some(rest)
+            } else {
+                rest.strip_prefix(' ')
+            }
+        }
+
+        let s = s.trim_start();
+        if let Some(message) = body(s, "CON") {
+            Ok(UssdResponse::Continue(message.to_string()))
+        } else if let Some(message) = body(s, "END") {
+            Ok(UssdResponse::End(message.to_string()))
+        } else {
+            Err(AfricasTalkingError::validation(
+                "USSD response must start with `CON` or `END`",
+            ))
+        }
+    }
+}
+
+/// Bytes a USSD screen reliably displays before the message is cut off
+pub const USSD_SCREEN_BYTES: usize = 182;
+
+/// Shorten `text` to fit on a single USSD screen
+///
+/// Text that fits is returned unchanged. Longer text is cut on a character
+/// boundary and ends with `...`, so the result never exceeds
+/// [`USSD_SCREEN_BYTES`] bytes.
+///
+/// ```rust
+/// use africastalking::ussd::{USSD_SCREEN_BYTES, truncate_for_screen};
+///
+/// let long = "a".repeat(300);
+/// let truncated = truncate_for_screen(&long);
+/// assert_eq!(truncated.len(), USSD_SCREEN_BYTES);
+/// assert!(truncated.ends_with("..."));
+///
+/// let emoji = "📱".repeat(60);
+/// assert!(truncate_for_screen(&emoji).len() <= USSD_SCREEN_BYTES);
+/// ```
+pub fn truncate_for_screen(text: &str) -> String {
+    const ELLIPSIS: &str = "...";
+
+    if text.len() <= USSD_SCREEN_BYTES {
+        return text.to_string();
+    }
+
+    let mut end = USSD_SCREEN_BYTES - ELLIPSIS.len();
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{ELLIPSIS}", &text[..end])
+}
+
+/// A numbered USSD menu
+///
+/// ```rust
+/// use africastalking::ussd::UssdMenu;
+///
+/// let menu = UssdMenu::new("Welcome")
+///     .option("1", "Check balance")
+///     .option("2", "Buy airtime");
+/// assert_eq!(menu.format_menu(), "Welcome\n1. Check balance\n2. Buy airtime");
+///
+/// let menu = UssdMenu::new("Account")
+///     .option("1", "Balance")
+///     .separator(")")
+///     .footer("0. Back  00. Main");
+/// assert_eq!(menu.format_menu(), "Account\n1) Balance\n0. Back  00. Main");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UssdMenu {
+    header: Option<String>,
+    title: String,
+    options: Vec<(String, String)>,
+    separator: Option<String>,
+    footer: Option<String>,
+    truncate: bool,
+}
+
+impl UssdMenu {
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        Self {
+            title: title.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Add an option selected by entering `key`
+    pub fn option<K: Into<String>, L: Into<String>>(mut self, key: K, label: L) -> Self {
+        self.options.push((key.into(), label.into()));
+        self
+    }
+
+    /// Set what follows each option key, `"."` by default
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = Some(separator.to_string());
+        self
+    }
+
+    /// Add a line above the title
+    pub fn header<S: Into<String>>(mut self, header: S) -> Self {
+        self.header = Some(header.into());
+        self
+    }
+
+    /// Add a line after the options, e.g. `"0. Back"`
+    pub fn footer<S: Into<String>>(mut self, footer: S) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
+    /// Cut the rendered menu to fit one screen with [`truncate_for_screen`]
+    pub fn truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Render the header and title followed by one `key. label` line per
+    /// option and the footer
+    pub fn format_menu(&self) -> String {
+        let separator = self.separator.as_deref().unwrap_or(".");
+
+        let mut lines = Vec::with_capacity(self.options.len() + 3);
+        lines.extend(self.header.clone());
+        if !self.title.is_empty() {
+            lines.push(self.title.clone());
+        }
+        lines.extend(
+            self.options
+                .iter()
+                .map(|(key, label)| format!("{key}{separator} {label}")),
+        );
+        lines.extend(self.footer.clone());
+
+        let menu = lines.join("\n");
+        if self.truncate {
+            truncate_for_screen(&menu)
+        } else {
+            menu
+        }
+    }
+}
+
+type Matcher = Box<dyn Fn(&UssdRequest) -> bool + Send + Sync>;
+type Handler = Box<dyn Fn(&UssdRequest) -> UssdResponse + Send + Sync>;
+
+/// Dispatches USSD callbacks to the first route whose matcher accepts them
+///
+/// ```rust
+/// use africastalking::ussd::{UssdRequest, UssdResponse, UssdRouter};
+///
+/// let router = UssdRouter::new()
+///     .on("", |_| UssdResponse::continues("1. Balance\n2. Exit"))
+///     .on("1", |_| UssdResponse::ends("Your balance is KES 10"))
+///     .fallback(|_| UssdResponse::ends("Invalid choice"));
+///
+/// let request = UssdRequest {
+///     session_id: "ATUid_1".into(),
+///     service_code: "*384#".into(),
+///     phone_number: "+254711000000".into(),
+///     text: "9".into(),
+///     network_code: "63902".into(),
+/// };
+/// assert_eq!(router.handle(&request), UssdResponse::ends("Invalid choice"));
+/// ```
+pub struct UssdRouter {
+    routes: Vec<(Matcher, Handler)>,
+    fallback: Handler,
+}
+
+impl UssdRouter {
+    /// Create a router that ends the session with "Invalid option" when
+    /// nothing matches
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            fallback: Box::new(|_| UssdResponse::ends("Invalid option")),
+        }
+    }
+
+    /// Add a route, checked in the order routes were added
+    pub fn route<M, H>(mut self, matcher: M, handler: H) -> Self
+    where
+        M: Fn(&UssdRequest) -> bool + Send + Sync + 'static,
+        H: Fn(&UssdRequest) -> UssdResponse + Send + Sync + 'static,
+    {
+        self.routes.push((Box::new(matcher), Box::new(handler)));
+        self
+    }
+
+    /// Add a route matching the session `text` exactly
+    pub fn on<S, H>(self, text: S, handler: H) -> Self
+    where
+        S: Into<String>,
+        H: Fn(&UssdRequest) -> UssdResponse + Send + Sync + 'static,
+    {
+        let text = text.into();
+        self.route(move |request| request.text == text, handler)
+    }
+
+    /// Set the handler used when no route matches
+    pub fn fallback<H>(mut self, handler: H) -> Self
+    where
+        H: Fn(&UssdRequest) -> UssdResponse + Send + Sync + 'static,
+    {
+        self.fallback = Box::new(handler);
+        self
+    }
+
+    /// Respond to a callback
+    pub fn handle(&self, request: &UssdRequest) -> UssdResponse {
+        self.routes
+            .iter()
+            .find(|(matcher, _)| matcher(request))
+            .map_or_else(|| (self.fallback)(request), |(_, handler)| handler(request))
+    }
+}
+
+impl Default for UssdRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for UssdRouter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UssdRouter")
+            .field("routes", &self.routes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Mobile network (MCC-MNC) codes sent with USSD callbacks
+///
+/// Serializes to and from the numeric code string, keeping unrecognized
+/// codes in [`NetworkCode::Unknown`].
+///
+/// ```rust
+/// use africastalking::ussd::NetworkCode;
+///
+/// let code: NetworkCode = serde_json::from_str(r#""63902""#).unwrap();
+/// assert_eq!(code, NetworkCode::SafaricomKenya);
+/// assert_eq!(serde_json::to_string(&code).unwrap(), r#""63902""#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkCode {
+    AirtelNigeria,
+    MtnNigeria,
+    GloNigeria,
+    EtisalatNigeria,
+    MtnRwanda,
+    TigoRwanda,
+    AirtelRwanda,
+    SafaricomKenya,
+    AirtelKenya,
+    TelkomKenya,
+    TigoTanzania,
+    ZantelTanzania,
+    VodacomTanzania,
+    AirtelTanzania,
+    TtclTanzania,
+    HalotelTanzania,
+    AirtelUganda,
+    MtnUganda,
+    UtlUganda,
+    AfricellUganda,
+    SmartUganda,
+    TnmMalawi,
+    AirtelMalawi,
+    VodacomSouthAfrica,
+    TelkomSouthAfrica,
+    CellCSouthAfrica,
+    MtnSouthAfrica,
+    RainSouthAfrica,
+    LycamobileSouthAfrica,
+    AirtelZambia,
+    MtnZambia,
+    ZamtelZambia,
+    EthioTelecomEthiopia,
+    /// Sandbox simulator
+    Athena,
+    /// A code not known to this version of the SDK
+    Unknown(String),
+}
+
+impl NetworkCode {
+    /// Parse a network code such as `"63902"`
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "62120" => NetworkCode::AirtelNigeria,
+            "62130" => NetworkCode::MtnNigeria,
+            "62150" => NetworkCode::GloNigeria,
+            "62160" => NetworkCode::EtisalatNigeria,
+            "63510" => NetworkCode::MtnRwanda,
+            "63513" => NetworkCode::TigoRwanda,
+            "63514" => NetworkCode::AirtelRwanda,
+            "63902" => NetworkCode::SafaricomKenya,
+            "63903" => NetworkCode::AirtelKenya,
+            "63907" => NetworkCode::TelkomKenya,
+            "64002" => NetworkCode::TigoTanzania,
+            "64003" => NetworkCode::ZantelTanzania,
+            "64004" => NetworkCode::VodacomTanzania,
+            "64005" => NetworkCode::AirtelTanzania,
+            "64007" => NetworkCode::TtclTanzania,
+            "64009" => NetworkCode::HalotelTanzania,
+            "64101" => NetworkCode::AirtelUganda,
+            "64110" => NetworkCode::MtnUganda,
+            "64111" => NetworkCode::UtlUganda,
+            "64114" => NetworkCode::AfricellUganda,
+            "64118" => NetworkCode::SmartUganda,
+            "65001" => NetworkCode::TnmMalawi,
+            "65010" => NetworkCode::AirtelMalawi,
+            "65501" => NetworkCode::VodacomSouthAfrica,
+            "65502" => NetworkCode::TelkomSouthAfrica,
+            "65507" => NetworkCode::CellCSouthAfrica,
+            "65510" => NetworkCode::MtnSouthAfrica,
+            "65538" => NetworkCode::RainSouthAfrica,
+            "65553" => NetworkCode::LycamobileSouthAfrica,
+            "64501" => NetworkCode::AirtelZambia,
+            "64502" => NetworkCode::MtnZambia,
+            "64503" => NetworkCode::ZamtelZambia,
+            "63601" => NetworkCode::EthioTelecomEthiopia,
+            "99999" => NetworkCode::Athena,
+            other => NetworkCode::Unknown(other.to_string()),
+        }
+    }
+
+    /// The network code as sent by AfricasTalking
+    pub fn as_code(&self) -> &str {
+        match self {
+            NetworkCode::AirtelNigeria => "62120",
+            NetworkCode::MtnNigeria => "62130",
+            NetworkCode::GloNigeria => "62150",
+            NetworkCode::EtisalatNigeria => "62160",
+            NetworkCode::MtnRwanda => "63510",
+            NetworkCode::TigoRwanda => "63513",
+            NetworkCode::AirtelRwanda => "63514",
+            NetworkCode::SafaricomKenya => "63902",
+            NetworkCode::AirtelKenya => "63903",
+            NetworkCode::TelkomKenya => "63907",
+            NetworkCode::TigoTanzania => "64002",
+            NetworkCode::ZantelTanzania => "64003",
+            NetworkCode::VodacomTanzania => "64004",
+            NetworkCode::AirtelTanzania => "64005",
+            NetworkCode::TtclTanzania => "64007",
+            NetworkCode::HalotelTanzania => "64009",
+            NetworkCode::AirtelUganda => "64101",
+            NetworkCode::MtnUganda => "64110",
+            NetworkCode::UtlUganda => "64111",
+            NetworkCode::AfricellUganda => "64114",
+            NetworkCode::SmartUganda => "64118",
+            NetworkCode::TnmMalawi => "65001",
+            NetworkCode::AirtelMalawi => "65010",
+            NetworkCode::VodacomSouthAfrica => "65501",
+            NetworkCode::TelkomSouthAfrica => "65502",
+            NetworkCode::CellCSouthAfrica => "65507",
+            NetworkCode::MtnSouthAfrica => "65510",
+            NetworkCode::RainSouthAfrica => "65538",
+            NetworkCode::LycamobileSouthAfrica => "65553",
+            NetworkCode::AirtelZambia => "64501",
+            NetworkCode::MtnZambia => "64502",
+            NetworkCode::ZamtelZambia => "64503",
+            NetworkCode::EthioTelecomEthiopia => "63601",
+            NetworkCode::Athena => "99999",
+            NetworkCode::Unknown(code) => code,
+        }
+    }
+
+    /// Human readable network name
+    pub fn name(&self) -> &str {
+        match self {
+            NetworkCode::AirtelNigeria
+            | NetworkCode::AirtelRwanda
+            | NetworkCode::AirtelKenya
+            | NetworkCode::AirtelTanzania
+            | NetworkCode::AirtelUganda
+            | NetworkCode::AirtelMalawi
+            | NetworkCode::AirtelZambia => "Airtel",
+            NetworkCode::MtnNigeria
+            | NetworkCode::MtnRwanda
+            | NetworkCode::MtnUganda
+            | NetworkCode::MtnSouthAfrica
+            | NetworkCode::MtnZambia => "MTN",
+            NetworkCode::GloNigeria => "Glo",
+            NetworkCode::EtisalatNigeria => "Etisalat",
+            NetworkCode::TigoRwanda | NetworkCode::TigoTanzania => "Tigo",
+            NetworkCode::SafaricomKenya => "Safaricom",
+            NetworkCode::ZantelTanzania => "Zantel",
+            NetworkCode::VodacomTanzania | NetworkCode::VodacomSouthAfrica => "Vodacom",
+            NetworkCode::UtlUganda => "UTL",
+            NetworkCode::AfricellUganda => "Africell",
+            NetworkCode::SmartUganda => "Smart",
+            NetworkCode::TtclTanzania => "TTCL",
+            NetworkCode::HalotelTanzania => "Halotel",
+            NetworkCode::RainSouthAfrica => "Rain",
+            NetworkCode::LycamobileSouthAfrica => "Lycamobile",
+            NetworkCode::ZamtelZambia => "Zamtel",
+            NetworkCode::TnmMalawi => "TNM",
+            NetworkCode::TelkomKenya | NetworkCode::TelkomSouthAfrica => "Telkom",
+            NetworkCode::CellCSouthAfrica => "Cell C",
+            NetworkCode::EthioTelecomEthiopia => "Ethio Telecom",
+            NetworkCode::Athena => "Athena",
+            NetworkCode::Unknown(_) => "Unknown",
+        }
+    }
+
+    /// Country the network operates in
+    pub fn country(&self) -> &str {
+        match self {
+            NetworkCode::AirtelNigeria
+            | NetworkCode::MtnNigeria
+            | NetworkCode::GloNigeria
+            | NetworkCode::EtisalatNigeria => "Nigeria",
+            NetworkCode::MtnRwanda | NetworkCode::TigoRwanda | NetworkCode::AirtelRwanda => {
+                "Rwanda"
+            }
+            NetworkCode::SafaricomKenya | NetworkCode::AirtelKenya | NetworkCode::TelkomKenya => {
+                "Kenya"
+            }
+            NetworkCode::TigoTanzania
+            | NetworkCode::ZantelTanzania
+            | NetworkCode::VodacomTanzania
+            | NetworkCode::AirtelTanzania
+            | NetworkCode::TtclTanzania
+            | NetworkCode::HalotelTanzania => "Tanzania",
+            NetworkCode::AirtelUganda
+            | NetworkCode::MtnUganda
+            | NetworkCode::UtlUganda
+            | NetworkCode::AfricellUganda
+            | NetworkCode::SmartUganda => "Uganda",
+            NetworkCode::TnmMalawi | NetworkCode::AirtelMalawi => "Malawi",
+            NetworkCode::VodacomSouthAfrica
+            | NetworkCode::TelkomSouthAfrica
+            | NetworkCode::CellCSouthAfrica
+            | NetworkCode::MtnSouthAfrica
+            | NetworkCode::RainSouthAfrica
+            | NetworkCode::LycamobileSouthAfrica => "South Africa",
+            NetworkCode::AirtelZambia | NetworkCode::MtnZambia | NetworkCode::ZamtelZambia => {
+                "Zambia"
+            }
+            NetworkCode::EthioTelecomEthiopia => "Ethiopia",
+            NetworkCode::Athena => "Sandbox",
+            NetworkCode::Unknown(_) => "Unknown",
+        }
+    }
+
+    /// ISO 3166 alpha-2 code of the network's country, e.g. `"KE"`
+    ///
+    /// Empty for the sandbox and unknown networks.
+    pub fn iso_code(&self) -> &str {
+        match self {
+            NetworkCode::AirtelNigeria
+            | NetworkCode::MtnNigeria
+            | NetworkCode::GloNigeria
+            | NetworkCode::EtisalatNigeria => "NG",
+            NetworkCode::MtnRwanda | NetworkCode::TigoRwanda | NetworkCode::AirtelRwanda => "RW",
+            NetworkCode::SafaricomKenya | NetworkCode::AirtelKenya | NetworkCode::TelkomKenya => {
+                "KE"
+            }
+            NetworkCode::TigoTanzania
+            | NetworkCode::ZantelTanzania
+            | NetworkCode::VodacomTanzania
+            | NetworkCode::AirtelTanzania
+            | NetworkCode::TtclTanzania
+            | NetworkCode::HalotelTanzania => "TZ",
+            NetworkCode::AirtelUganda
+            | NetworkCode::MtnUganda
+            | NetworkCode::UtlUganda
+            | NetworkCode::AfricellUganda
+            | NetworkCode::SmartUganda => "UG",
+            NetworkCode::TnmMalawi | NetworkCode::AirtelMalawi => "MW",
+            NetworkCode::VodacomSouthAfrica
+            | NetworkCode::TelkomSouthAfrica
+            | NetworkCode::CellCSouthAfrica
+            | NetworkCode::MtnSouthAfrica
+            | NetworkCode::RainSouthAfrica
+            | NetworkCode::LycamobileSouthAfrica => "ZA",
+            NetworkCode::AirtelZambia | NetworkCode::MtnZambia | NetworkCode::ZamtelZambia => "ZM",
+            NetworkCode::EthioTelecomEthiopia => "ET",
+            NetworkCode::Athena | NetworkCode::Unknown(_) => "",
+        }
+    }
+
+    /// E.164 dialing code of the network's country without the `+`, e.g.
+    /// `"254"`
+    ///
+    /// Empty for the sandbox and unknown networks.
+    ///
+    /// ```rust
+    /// use africastalking::ussd::NetworkCode;
+    ///
+    /// assert_eq!(NetworkCode::SafaricomKenya.iso_code(), "KE");
+    /// assert_eq!(NetworkCode::SafaricomKenya.dialing_code(), "254");
+    /// ```
+    pub fn dialing_code(&self) -> &str {
+        dialing_code(self.iso_code()).unwrap_or_default()
+    }
+}
+
+impl Serialize for NetworkCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_code())
+    }
+}
+
+impl<'de> Deserialize<'de> for NetworkCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Ok(NetworkCode::from_code(&code))
+    }
+}
+
+/// Storage for per-session state between USSD callbacks, keyed by `sessionId`
+pub trait UssdSessionStore<T>: Send + Sync {
+    /// Load the state saved for a session
+    fn get(&self, session_id: &str) -> impl Future<Output = Result<Option<T>>> + Send;
+
+    /// Save the state for a session, replacing any previous value
+    fn set(&self, session_id: &str, session: T) -> impl Future<Output = Result<()>> + Send;
+
+    /// Drop the state for a session, typically once it has ended
+    fn clear(&self, session_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Scope the store to one session, clearing it once the handler ends
+    /// the session
+    fn guard<'a>(&'a self, session_id: &'a str) -> UssdSessionGuard<'a, Self, T>
+    where
+        Self: Sized,
+    {
+        UssdSessionGuard {
+            store: self,
+            session_id,
+            _session: PhantomData,
+        }
+    }
+}
+
+/// A [`UssdSessionStore`] scoped to one session
+///
+/// Pass the handler's response through [`finish`](Self::finish) so the
+/// session is cleared on `END`. A guard dropped without finishing leaves the
+/// session to the store's expiry, since clearing needs an `await`.
+///
+/// ```rust
+/// use africastalking::ussd::{InMemorySessionStore, UssdResponse, UssdSessionStore};
+///
+/// # #[tokio::main]
+/// # async fn main() -> africastalking::Result<()> {
+/// let store = InMemorySessionStore::<u32>::new();
+///
+/// let session = store.guard("ATUid_1");
+/// session.set(1).await?;
+/// session.finish(UssdResponse::continues("Enter amount")).await?;
+/// assert_eq!(store.get("ATUid_1").await?, Some(1));
+///
+/// let session = store.guard("ATUid_1");
+/// session.finish(UssdResponse::ends("Thank you")).await?;
+/// assert_eq!(store.get("ATUid_1").await?, None);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct UssdSessionGuard<'a, S, T> {
+    store: &'a S,
+    session_id: &'a str,
+    _session: PhantomData<fn() -> T>,
+}
+
+impl<S: UssdSessionStore<T>, T> UssdSessionGuard<'_, S, T> {
+    /// Load the state saved for this session
+    pub async fn get(&self) -> Result<Option<T>> {
+        self.store.get(self.session_id).await
+    }
+
+    /// Save the state for this session
+    pub async fn set(&self, session: T) -> Result<()> {
+        self.store.set(self.session_id, session).await
+    }
+
+    /// Return `response`, clearing the session first if it ends it
+    pub async fn finish(self, response: UssdResponse) -> Result<UssdResponse> {
+        if response.is_ending() {
+            self.store.clear(self.session_id).await?;
+        }
+        Ok(response)
+    }
+}
+
+/// Process-local [`UssdSessionStore`]
+///
+/// Clones share the same sessions. Entries expire after the configured TTL,
+/// which defaults to five minutes.
+///
+/// ```rust
+/// use africastalking::ussd::{InMemorySessionStore, UssdSessionStore};
+///
+/// # #[tokio::main]
+/// # async fn main() -> africastalking::Result<()> {
+/// let store = InMemorySessionStore::<Vec<String>>::new();
+/// store.set("ATUid_1", vec!["1".to_string()]).await?;
+/// assert_eq!(store.get("ATUid_1").await?, Some(vec!["1".to_string()]));
+///
+/// store.clear("ATUid_1").await?;
+/// assert_eq!(store.get("ATUid_1").await?, None);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InMemorySessionStore<T> {
+    sessions: Arc<RwLock<HashMap<String, (T, Instant)>>>,
+    ttl: Duration,
+}
+
+impl<T> InMemorySessionStore<T> {
+    pub fn new() -> Self {
+        Self::with_ttl(Duration::from_secs(300))
+    }
+
+    /// Create a store whose sessions expire after `ttl`
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            ttl,
+        }
+    }
+}
+
+impl<T> Default for InMemorySessionStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + Send + Sync> UssdSessionStore<T> for InMemorySessionStore<T> {
+    async fn get(&self, session_id: &str) -> Result<Option<T>> {
+        let sessions = self.sessions.read().await;
+        Ok(sessions
+            .get(session_id)
+            .filter(|(_, expires_at)| *expires_at > Instant::now())
+            .map(|(session, _)| session.clone()))
+    }
+
+    async fn set(&self, session_id: &str, session: T) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
+        let now = Instant::now();
+        sessions.retain(|_, (_, expires_at)| *expires_at > now);
+        sessions.insert(session_id.to_string(), (session, now + self.ttl));
+        Ok(())
+    }
+
+    async fn clear(&self, session_id: &str) -> Result<()> {
+        self.sessions.write().await.remove(session_id);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use redis_store::RedisSessionStore;
+
+#[cfg(feature = "redis")]
+mod redis_store {
+    use super::UssdSessionStore;
+    use crate::error::{AfricasTalkingError, Result};
+    use redis::{AsyncCommands, aio::MultiplexedConnection};
+    use serde::{Serialize, de::DeserializeOwned};
+    use std::{marker::PhantomData, time::Duration};
+
+    /// [`UssdSessionStore`] backed by Redis, storing sessions as JSON
+    pub struct RedisSessionStore<T> {
+        connection: MultiplexedConnection,
+        prefix: String,
+        ttl: Duration,
+        _session: PhantomData<fn() -> T>,
+    }
+
+    impl<T> RedisSessionStore<T> {
+        /// Connect to Redis at `url`, e.g. `redis://127.0.0.1/`
+        pub async fn connect(url: &str) -> Result<Self> {
+            let client = redis::Client::open(url).map_err(redis_error)?;
+            let connection = client
+                .get_multiplexed_async_connection()
+                .await
+                .map_err(redis_error)?;
+
+            Ok(Self {
+                connection,
+                prefix: "ussd:session:".to_string(),
+                ttl: Duration::from_secs(300),
+                _session: PhantomData,
+            })
+        }
+
+        /// Set the prefix added to session ids to form Redis keys
+        pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+            self.prefix = prefix.into();
+            self
+        }
+
+        /// Set how long sessions are kept
+        pub fn ttl(mut self, ttl: Duration) -> Self {
+            self.ttl = ttl;
+            self
+        }
+
+        fn key(&self, session_id: &str) -> String {
+            format!("{}{}", self.prefix, session_id)
+        }
+    }
+
+    impl<T> Clone for RedisSessionStore<T> {
+        fn clone(&self) -> Self {
+            Self {
+                connection: self.connection.clone(),
+                prefix: self.prefix.clone(),
+                ttl: self.ttl,
+                _session: PhantomData,
+            }
+        }
+    }
+
+    impl<T> UssdSessionStore<T> for RedisSessionStore<T>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync,
+    {
+        async fn get(&self, session_id: &str) -> Result<Option<T>> {
+            let mut connection = self.connection.clone();
+            let value: Option<String> = connection
+                .get(self.key(session_id))
+                .await
+                .map_err(redis_error)?;
+
+            value
+                .map(|json| serde_json::from_str(&json))
+                .transpose()
+                .map_err(Into::into)
+        }
+
+        async fn set(&self, session_id: &str, session: T) -> Result<()> {
+            let json = serde_json::to_string(&session)?;
+            let mut connection = self.connection.clone();
+            connection
+                .set_ex::<_, _, ()>(self.key(session_id), json, self.ttl.as_secs().max(1))
+                .await
+                .map_err(redis_error)
+        }
+
+        async fn clear(&self, session_id: &str) -> Result<()> {
+            let mut connection = self.connection.clone();
+            connection
+                .del::<_, ()>(self.key(session_id))
+                .await
+                .map_err(redis_error)
+        }
+    }
+
+    fn redis_error(error: redis::RedisError) -> AfricasTalkingError {
+        AfricasTalkingError::Internal(format!("Redis error: {error}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(text: &str) -> UssdRequest {
+        UssdRequest {
+            session_id: "ATUid_1".into(),
+            service_code: "*384#".into(),
+            phone_number: "+254711082000".into(),
+            text: text.into(),
+            network_code: "63902".into(),
+        }
+    }
+
+    #[test]
+    fn parses_responses_with_leading_whitespace() {
+        assert_eq!(
+            "  \r\nCON Choose an option"
+                .parse::<UssdResponse>()
+                .unwrap(),
+            UssdResponse::Continue("Choose an option".into())
+        );
+        assert_eq!(
+            "\tEND Thank you".parse::<UssdResponse>().unwrap(),
+            UssdResponse::End("Thank you".into())
+        );
+    }
+
+    #[test]
+    fn only_the_leading_keyword_sets_the_kind() {
+        assert_eq!(
+            "END CON was not the prefix"
+                .parse::<UssdResponse>()
+                .unwrap(),
+            UssdResponse::End("CON was not the prefix".into())
+        );
+        assert_eq!(
+            "CON Reply CON to continue\n1. CONFIRM"
+                .parse::<UssdResponse>()
+                .unwrap(),
+            UssdResponse::Continue("Reply CON to continue\n1. CONFIRM".into())
+        );
+        assert_eq!(
+            "END".parse::<UssdResponse>().unwrap(),
+            UssdResponse::End(String::new())
+        );
+    }
+
+    #[test]
+    fn rejects_responses_without_a_keyword() {
+        for raw in ["CONTINUE", "ENDED", "Welcome CON", "con lowercase", ""] {
+            assert!(
+                matches!(
+                    raw.parse::<UssdResponse>(),
+                    Err(AfricasTalkingError::Validation(_))
+                ),
+                "{raw:?} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for response in [
+            UssdResponse::Continue("1. Balance\n2. Airtime".into()),
+            UssdResponse::End("Goodbye".into()),
+        ] {
+            assert_eq!(
+                response.to_string().parse::<UssdResponse>().unwrap(),
+                response
+            );
+        }
+    }
+
+    #[test]
+    fn from_code_names_the_newer_networks() {
+        let cases = [
+            ("63907", NetworkCode::TelkomKenya, "Telkom", "Kenya"),
+            ("64007", NetworkCode::TtclTanzania, "TTCL", "Tanzania"),
+            ("64009", NetworkCode::HalotelTanzania, "Halotel", "Tanzania"),
+            ("64114", NetworkCode::AfricellUganda, "Africell", "Uganda"),
+            ("64118", NetworkCode::SmartUganda, "Smart", "Uganda"),
+            ("65001", NetworkCode::TnmMalawi, "TNM", "Malawi"),
+            ("65010", NetworkCode::AirtelMalawi, "Airtel", "Malawi"),
+            (
+                "65502",
+                NetworkCode::TelkomSouthAfrica,
+                "Telkom",
+                "South Africa",
+            ),
+            (
+                "65538",
+                NetworkCode::RainSouthAfrica,
+                "Rain",
+                "South Africa",
+            ),
+            (
+                "65553",
+                NetworkCode::LycamobileSouthAfrica,
+                "Lycamobile",
+                "South Africa",
+            ),
+            ("64501", NetworkCode::AirtelZambia, "Airtel", "Zambia"),
+            ("64502", NetworkCode::MtnZambia, "MTN", "Zambia"),
+            ("64503", NetworkCode::ZamtelZambia, "Zamtel", "Zambia"),
+            (
+                "63601",
+                NetworkCode::EthioTelecomEthiopia,
+                "Ethio Telecom",
+                "Ethiopia",
+            ),
+        ];
+        for (code, network, name, country) in cases {
+            assert_eq!(NetworkCode::from_code(code), network, "{code}");
+            assert_eq!(network.as_code(), code);
+            assert_eq!(network.name(), name);
+            assert_eq!(network.country(), country);
+        }
+    }
+
+    #[test]
+    fn current_input_as_parses_the_last_input() {
+        assert_eq!(request("1*2*42").current_input_as::<u32>(), Some(42));
+        assert_eq!(request("1* 7 ").current_input_as::<u8>(), Some(7));
+        assert_eq!(request("1*abc").current_input_as::<u32>(), None);
+        assert_eq!(request("1*300").current_input_as::<u8>(), None);
+        assert_eq!(request("").current_input_as::<u32>(), None);
+    }
+
+    #[test]
+    fn current_input_amount_checks_the_range() {
+        assert_eq!(
+            request("2*250.50").current_input_amount(10.0, 1000.0),
+            Ok(250.5)
+        );
+        assert_eq!(request("2*10").current_input_amount(10.0, 1000.0), Ok(10.0));
+        assert_eq!(
+            request("2*1000").current_input_amount(10.0, 1000.0),
+            Ok(1000.0)
+        );
+        assert_eq!(
+            request("2*5").current_input_amount(10.0, 1000.0),
+            Err("Amount is below the minimum")
+        );
+        assert_eq!(
+            request("2*1000.01").current_input_amount(10.0, 1000.0),
+            Err("Amount is above the maximum")
+        );
+    }
+
+    #[test]
+    fn current_input_amount_rejects_non_numbers() {
+        for text in ["2*abc", "2*", "2*NaN", "2*inf", "2*-inf", "2*1e400"] {
+            assert_eq!(
+                request(text).current_input_amount(f64::MIN, f64::MAX),
+                Err("Please enter a valid amount"),
+                "{text:?}"
+            );
+        }
+    }
+
+    fn notification(cost: &str, duration_in_millis: &str) -> UssdNotification {
+        serde_json::from_value(serde_json::json!({
+            "date": "2024-03-14 09:41:22",
+            "sessionId": "ATUid_1",
+            "serviceCode": "*384#",
+            "networkCode": "63902",
+            "phoneNumber": "+254711082000",
+            "status": "Success",
+            "cost": cost,
+            "durationInMillis": duration_in_millis
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn notification_parses_cost_and_duration() {
+        let notification = notification("KES 0.50", "12000");
+        assert_eq!(notification.parsed_cost().unwrap(), (Currency::Kes, 0.5));
+        assert_eq!(notification.duration().unwrap(), Duration::from_secs(12));
+    }
+
+    #[test]
+    fn notification_rejects_malformed_cost_and_duration() {
+        for cost in ["0.50", "KES", "KES abc", "XYZ 0.50"] {
+            let error = notification(cost, "12000").parsed_cost().unwrap_err();
+            assert!(
+                matches!(error, AfricasTalkingError::Validation(_)),
+                "{cost:?}"
+            );
+        }
+        for duration in ["", "12s", "-1"] {
+            assert!(notification("KES 0.50", duration).duration().is_err());
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn in_memory_sessions_expire_after_the_ttl() {
+        let store = InMemorySessionStore::with_ttl(Duration::from_secs(60));
+        store.set("ATUid_1", 1u32).await.unwrap();
+
+        tokio::time::advance(Duration::from_secs(59)).await;
+        assert_eq!(store.get("ATUid_1").await.unwrap(), Some(1));
+
+        // Saving again restarts the clock
+        store.set("ATUid_1", 2).await.unwrap();
+        tokio::time::advance(Duration::from_secs(59)).await;
+        assert_eq!(store.get("ATUid_1").await.unwrap(), Some(2));
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(store.get("ATUid_1").await.unwrap(), None);
+    }
+
+    #[test]
+    fn router_matches_exact_text() {
+        let router = UssdRouter::new()
+            .on("", |_| UssdResponse::continues("1. Balance"))
+            .on("1", |_| UssdResponse::ends("Balance: KES 10"));
+
+        assert_eq!(
+            router.handle(&request("")),
+            UssdResponse::continues("1. Balance")
+        );
+        assert_eq!(
+            router.handle(&request("1")),
+            UssdResponse::ends("Balance: KES 10")
+        );
+        assert_eq!(
+            router.handle(&request("1*1")),
+            UssdResponse::ends("Invalid option")
+        );
+    }
+
+    #[test]
+    fn router_uses_the_first_matching_route() {
+        let router = UssdRouter::new()
+            .route(
+                |request| request.text.starts_with('2'),
+                |_| UssdResponse::ends("first"),
+            )
+            .on("2", |_| UssdResponse::ends("second"));
+
+        assert_eq!(router.handle(&request("2")), UssdResponse::ends("first"));
+    }
+
+    #[test]
+    fn router_matches_on_phone_and_network() {
+        let router = UssdRouter::new()
+            .route(
+                |request| request.phone_number == "+254722000000",
+                |_| UssdResponse::ends("Welcome back"),
+            )
+            .route(
+                |request| request.network() == NetworkCode::AirtelKenya,
+                |request| UssdResponse::ends(format!("Hello {}", request.network().name())),
+            )
+            .fallback(|_| UssdResponse::ends("Not supported"));
+
+        let mut known_caller = request("");
+        known_caller.phone_number = "+254722000000".into();
+        assert_eq!(
+            router.handle(&known_caller),
+            UssdResponse::ends("Welcome back")
+        );
+
+        let mut airtel = request("");
+        airtel.network_code = "63903".into();
+        assert_eq!(router.handle(&airtel), UssdResponse::ends("Hello Airtel"));
+
+        assert_eq!(
+            router.handle(&request("")),
+            UssdResponse::ends("Not supported")
+        );
+    }
+
+    #[test]
+    fn unknown_network_codes_round_trip() {
+        let code: NetworkCode = serde_json::from_str(r#""99901""#).unwrap();
+        assert_eq!(code, NetworkCode::Unknown("99901".into()));
+        assert_eq!(code.name(), "Unknown");
+        assert_eq!(serde_json::to_string(&code).unwrap(), r#""99901""#);
+    }
+}
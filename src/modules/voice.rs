@@ -0,0 +1,1858 @@
+//! Voice module implementation
+
+use crate::{
+    client::AfricasTalkingClient,
+    error::{AfricasTalkingError, Result},
+    types::{AtResponse, PhoneNumber, normalize_phone_numbers},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[cfg(feature = "media-upload")]
+use reqwest::multipart::{Form, Part};
+#[cfg(feature = "media-upload")]
+use std::path::Path;
+
+/// Voice module for making calls and managing call queues
+#[derive(Debug, Clone)]
+pub struct VoiceModule {
+    client: AfricasTalkingClient,
+}
+
+impl VoiceModule {
+    pub(crate) fn new(client: AfricasTalkingClient) -> Self {
+        Self { client }
+    }
+
+    /// Make an outbound call to one or more recipients
+    pub async fn make_call(&self, mut request: MakeCallRequest) -> Result<MakeCallResponse> {
+        request.validate()?;
+        request.username = self.client.config.username.clone();
+        self.client.post_json("/call", &request).await
+    }
+
+    /// Get the number of queued calls for one or more phone numbers
+    pub async fn get_queued_calls(
+        &self,
+        mut request: QueueStatusRequest,
+    ) -> Result<QueueStatusResponse> {
+        request.username = self.client.config.username.clone();
+        self.client.post_json("/queueStatus", &request).await
+    }
+
+    /// Upload a media file to be played during calls
+    pub async fn upload_media(
+        &self,
+        mut request: UploadMediaRequest,
+    ) -> Result<UploadMediaResponse> {
+        request.username = self.client.config.username.clone();
+        self.client.post_json("/mediaUpload", &request).await
+    }
+
+    /// Upload a local audio file (e.g. `.mp3` or `.wav`) as a multipart form
+    ///
+    /// The form carries `username`, `phoneNumber` and the file under
+    /// [`MEDIA_FILE_FIELD`]. AfricasTalking's public reference only documents
+    /// the URL form of `/mediaUpload` ([`upload_media`](Self::upload_media)),
+    /// so check uploads against the sandbox first and fall back to a hosted URL
+    /// if the gateway rejects the form.
+    ///
+    /// The file is read once and the form rebuilt for each attempt, so uploads
+    /// are retried and rate limited like any other request. A missing file is a
+    /// [`AfricasTalkingError::Validation`] error.
+    ///
+    /// Needs the `media-upload` feature, since the field names are not
+    /// confirmed by AfricasTalking.
+    #[cfg(feature = "media-upload")]
+    pub async fn upload_media_file(
+        &self,
+        path: impl AsRef<Path>,
+        phone_number: &str,
+    ) -> Result<UploadMediaResponse> {
+        let path = path.as_ref();
+        let data = tokio::fs::read(path).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                AfricasTalkingError::validation(format!("Media file not found: {}", path.display()))
+            } else {
+                AfricasTalkingError::Internal(format!("Failed to read {}: {e}", path.display()))
+            }
+        })?;
+
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "media".to_string());
+        let content_type = match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("mp3") => "audio/mpeg",
+            Some("wav") => "audio/wav",
+            Some("ogg") => "audio/ogg",
+            Some("m4a") => "audio/mp4",
+            _ => "application/octet-stream",
+        };
+
+        let build_form = || {
+            let file = Part::bytes(data.clone())
+                .file_name(file_name.clone())
+                .mime_str(content_type)?;
+            Ok(Form::new()
+                .text("username", self.client.config.username.clone())
+                .text("phoneNumber", phone_number.to_string())
+                .part(MEDIA_FILE_FIELD, file))
+        };
+
+        self.client.post_multipart("/mediaUpload", build_form).await
+    }
+}
+
+/// Name of the multipart part holding the audio in
+/// [`VoiceModule::upload_media_file`], following the camelCase naming of the
+/// other `/mediaUpload` fields
+#[cfg(feature = "media-upload")]
+pub const MEDIA_FILE_FIELD: &str = "mediaFile";
+
+#[derive(Debug, Serialize)]
+pub struct MakeCallRequest {
+    pub username: String,
+    #[serde(rename = "from")]
+    pub call_from: String,
+    #[serde(rename = "to")]
+    pub call_to: String,
+    #[serde(rename = "clientRequestId", skip_serializing_if = "Option::is_none")]
+    pub client_request_id: Option<String>,
+}
+
+impl MakeCallRequest {
+    pub fn new<S: Into<String>>(call_from: S, call_to: Vec<S>) -> Self {
+        Self {
+            username: String::new(), // Set from `Config` by `VoiceModule`
+            call_from: call_from.into(),
+            call_to: call_to
+                .into_iter()
+                .map(Into::into)
+                .collect::<Vec<String>>()
+                .join(","),
+            client_request_id: None,
+        }
+    }
+
+    /// Create a request, normalizing both numbers to E.164 first
+    ///
+    /// Numbers in local format get the dialing code of `default_country`.
+    ///
+    /// ```rust
+    /// use africastalking::voice::MakeCallRequest;
+    ///
+    /// let request = MakeCallRequest::new_checked("+254711082000", vec!["0712345678"], "KE").unwrap();
+    /// assert_eq!(request.call_to, "+254712345678");
+    /// ```
+    pub fn new_checked<S: Into<String>>(
+        call_from: S,
+        call_to: Vec<S>,
+        default_country: &str,
+    ) -> Result<Self> {
+        let call_from = PhoneNumber::parse(&call_from.into(), default_country)?.as_e164();
+        let call_to = normalize_phone_numbers(call_to, default_country)?;
+        Ok(Self::new(call_from, call_to))
+    }
+
+    pub fn client_request_id<S: Into<String>>(mut self, client_request_id: S) -> Self {
+        self.client_request_id = Some(client_request_id.into());
+        self
+    }
+
+    /// Check there is at least one recipient and that every recipient is an
+    /// E.164 number such as `+254711000000`
+    pub fn validate(&self) -> Result<()> {
+        let recipients = self
+            .call_to
+            .split(',')
+            .map(str::trim)
+            .filter(|number| !number.is_empty())
+            .collect::<Vec<_>>();
+
+        if recipients.is_empty() {
+            return Err(AfricasTalkingError::validation(
+                "A call needs at least one recipient",
+            ));
+        }
+
+        if let Some(number) = recipients.iter().find(|number| !is_e164(number)) {
+            return Err(AfricasTalkingError::validation(format!(
+                "Recipient {number:?} is not an E.164 phone number"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// `+` followed by 8 to 15 digits, the first of which is not zero
+fn is_e164(number: &str) -> bool {
+    number.strip_prefix('+').is_some_and(|digits| {
+        (8..=15).contains(&digits.len())
+            && !digits.starts_with('0')
+            && digits.bytes().all(|b| b.is_ascii_digit())
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MakeCallResponse {
+    #[serde(default)]
+    pub entries: Vec<CallEntry>,
+    #[serde(rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+impl AtResponse for MakeCallResponse {
+    fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallEntry {
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+    pub status: CallStatus,
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<String>,
+}
+
+/// Per-recipient status returned when making a call
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "String")]
+#[non_exhaustive]
+pub enum CallStatus {
+    Queued,
+    InvalidPhoneNumber,
+    DestinationNotSupported,
+    InsufficientCredit,
+    /// A status not known to this version of the SDK
+    Unknown(String),
+}
+
+impl CallStatus {
+    /// Whether the call was accepted
+    pub fn is_queued(&self) -> bool {
+        matches!(self, CallStatus::Queued)
+    }
+}
+
+impl From<String> for CallStatus {
+    fn from(status: String) -> Self {
+        // Some responses spell the statuses out with spaces
+        match status.replace(' ', "").as_str() {
+            "Queued" => CallStatus::Queued,
+            "InvalidPhoneNumber" => CallStatus::InvalidPhoneNumber,
+            "DestinationNotSupported" => CallStatus::DestinationNotSupported,
+            "InsufficientCredit" => CallStatus::InsufficientCredit,
+            _ => CallStatus::Unknown(status),
+        }
+    }
+}
+
+impl fmt::Display for CallStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallStatus::Queued => f.write_str("Queued"),
+            CallStatus::InvalidPhoneNumber => f.write_str("InvalidPhoneNumber"),
+            CallStatus::DestinationNotSupported => f.write_str("DestinationNotSupported"),
+            CallStatus::InsufficientCredit => f.write_str("InsufficientCredit"),
+            CallStatus::Unknown(status) => f.write_str(status),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueueStatusRequest {
+    pub username: String,
+    #[serde(rename = "phoneNumbers")]
+    pub phone_numbers: String,
+}
+
+impl QueueStatusRequest {
+    pub fn new<S: Into<String>>(phone_numbers: Vec<S>) -> Self {
+        Self {
+            username: String::new(), // Set from `Config` by `VoiceModule`
+            phone_numbers: phone_numbers
+                .into_iter()
+                .map(Into::into)
+                .collect::<Vec<String>>()
+                .join(","),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueueStatusResponse {
+    #[serde(default)]
+    pub status: String,
+    #[serde(default, alias = "phoneNumbers")]
+    pub entries: Vec<QueueStatusEntry>,
+    /// Total queued calls, when AfricasTalking reports it directly
+    #[serde(rename = "NumQueuedCalls", alias = "numQueuedCalls")]
+    pub num_queued_calls: Option<u32>,
+    #[serde(rename = "errorMessage", default)]
+    pub error_message: Option<String>,
+}
+
+impl AtResponse for QueueStatusResponse {
+    fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+}
+
+impl QueueStatusResponse {
+    /// Total queued calls, summing the per-number counts when no total is
+    /// reported
+    pub fn total_queued_calls(&self) -> u32 {
+        self.num_queued_calls
+            .unwrap_or_else(|| self.entries.iter().map(|entry| entry.num_calls).sum())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueueStatusEntry {
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+    #[serde(rename = "queueName")]
+    pub queue_name: Option<String>,
+    #[serde(rename = "numCalls")]
+    pub num_calls: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadMediaRequest {
+    pub username: String,
+    pub url: String,
+    #[serde(rename = "phoneNumber")]
+    pub phone_number: String,
+}
+
+impl UploadMediaRequest {
+    pub fn new<S: Into<String>>(url: S, phone_number: S) -> Self {
+        Self {
+            username: String::new(), // Set from `Config` by `VoiceModule`
+            url: url.into(),
+            phone_number: phone_number.into(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadMediaResponse {
+    #[serde(rename = "errorMessage")]
+    pub error_message: Option<String>,
+}
+
+impl AtResponse for UploadMediaResponse {
+    fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+}
+
+/// Call notification AfricasTalking posts to the voice callback URL
+#[derive(Debug, Clone, Deserialize)]
+pub struct VoiceNotification {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    /// `"1"` while the call is in progress, `"0"` once it has ended
+    #[serde(rename = "isActive")]
+    pub is_active: Option<String>,
+    /// `Inbound` or `Outbound`
+    pub direction: Option<String>,
+    #[serde(rename = "callerNumber")]
+    pub caller_number: Option<String>,
+    #[serde(rename = "destinationNumber")]
+    pub destination_number: Option<String>,
+    pub status: Option<VoiceCallStatus>,
+    #[serde(rename = "callSessionState")]
+    pub call_session_state: Option<String>,
+    #[serde(rename = "durationInSeconds")]
+    pub duration_in_seconds: Option<u32>,
+    #[serde(rename = "currencyCode")]
+    pub currency_code: Option<String>,
+    pub amount: Option<String>,
+    #[serde(rename = "recordingUrl")]
+    pub recording_url: Option<String>,
+    #[serde(rename = "dtmfDigits")]
+    pub dtmf_digits: Option<String>,
+    #[serde(rename = "hangupCause")]
+    pub hangup_cause: Option<HangupCause>,
+    #[serde(rename = "callStartTime")]
+    pub call_start_time: Option<String>,
+}
+
+impl VoiceNotification {
+    /// Whether the call is still in progress
+    pub fn is_active(&self) -> bool {
+        self.is_active.as_deref() == Some("1")
+    }
+}
+
+/// Final status of a call in a voice notification
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "String")]
+#[non_exhaustive]
+pub enum VoiceCallStatus {
+    Success,
+    Failed,
+    Aborted,
+    /// A status not known to this version of the SDK
+    Unknown(String),
+}
+
+impl From<String> for VoiceCallStatus {
+    fn from(status: String) -> Self {
+        match status.as_str() {
+            "Success" => VoiceCallStatus::Success,
+            "Failed" => VoiceCallStatus::Failed,
+            "Aborted" => VoiceCallStatus::Aborted,
+            _ => VoiceCallStatus::Unknown(status),
+        }
+    }
+}
+
+/// Reason a call ended
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "String")]
+#[non_exhaustive]
+pub enum HangupCause {
+    NormalClearing,
+    NoAnswer,
+    UserBusy,
+    CallRejected,
+    OriginatorCancel,
+    UnallocatedNumber,
+    InvalidNumberFormat,
+    NormalTemporaryFailure,
+    RecoveryOnTimerExpire,
+    SubscriberAbsent,
+    /// A cause not known to this version of the SDK
+    Unknown(String),
+}
+
+impl From<String> for HangupCause {
+    fn from(cause: String) -> Self {
+        match cause.as_str() {
+            "NORMAL_CLEARING" => HangupCause::NormalClearing,
+            "NO_ANSWER" => HangupCause::NoAnswer,
+            "USER_BUSY" => HangupCause::UserBusy,
+            "CALL_REJECTED" => HangupCause::CallRejected,
+            "ORIGINATOR_CANCEL" => HangupCause::OriginatorCancel,
+            "UNALLOCATED_NUMBER" => HangupCause::UnallocatedNumber,
+            "INVALID_NUMBER_FORMAT" => HangupCause::InvalidNumberFormat,
+            "NORMAL_TEMPORARY_FAILURE" => HangupCause::NormalTemporaryFailure,
+            "RECOVERY_ON_TIMER_EXPIRE" => HangupCause::RecoveryOnTimerExpire,
+            "SUBSCRIBER_ABSENT" => HangupCause::SubscriberAbsent,
+            _ => HangupCause::Unknown(cause),
+        }
+    }
+}
+
+// --- Voice action XML builder ---
+
+/// Escape a value for use in XML text or attribute content
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// SSML tags `ActionBuilder::say_ssml` passes through unescaped
+const SSML_TAGS: &[&str] = &["break", "emphasis", "prosody", "say-as", "p", "s", "sub"];
+
+/// XML entities left as they are in SSML text
+const XML_ENTITIES: &[(&str, char)] = &[
+    ("&lt;", '<'),
+    ("&gt;", '>'),
+    ("&quot;", '"'),
+    ("&apos;", '\''),
+    ("&amp;", '&'),
+];
+
+/// Escape SSML text, keeping whitelisted tags and existing entities intact
+///
+/// Tags are re-rendered with escaped attributes. Closing tags that don't
+/// match the innermost open tag are escaped as text, and tags left open are
+/// closed at the end, so the output is always well-formed.
+fn escape_ssml(ssml: &str) -> String {
+    let mut escaped = String::with_capacity(ssml.len());
+    let mut open_tags = Vec::new();
+    let mut rest = ssml;
+    while let Some(c) = rest.chars().next() {
+        let mut len = c.len_utf8();
+        match c {
+            '<' => match SsmlTag::parse(rest) {
+                Some(tag) => {
+                    len = tag.len;
+                    if !tag.closing {
+                        escaped.push_str(&tag.to_xml());
+                        if !tag.self_closing {
+                            open_tags.push(tag.name);
+                        }
+                    } else if open_tags.last() == Some(&tag.name) {
+                        open_tags.pop();
+                        escaped.push_str(&tag.to_xml());
+                    } else {
+                        escaped.push_str(&escape_xml(&rest[..len]));
+                    }
+                }
+                None => escaped.push_str("&lt;"),
+            },
+            '>' => escaped.push_str("&gt;"),
+            '&' if XML_ENTITIES
+                .iter()
+                .any(|(entity, _)| rest.starts_with(entity)) =>
+            {
+                escaped.push('&')
+            }
+            '&' => escaped.push_str("&amp;"),
+            _ => escaped.push(c),
+        }
+        rest = &rest[len..];
+    }
+
+    for name in open_tags.into_iter().rev() {
+        escaped.push_str(&format!("</{name}>"));
+    }
+    escaped
+}
+
+/// A whitelisted SSML tag found by `escape_ssml`
+struct SsmlTag<'a> {
+    name: &'a str,
+    /// Attribute names and their raw, possibly entity-encoded, values
+    attributes: Vec<(&'a str, &'a str)>,
+    closing: bool,
+    self_closing: bool,
+    /// Length of the tag in the input, including the angle brackets
+    len: usize,
+}
+
+impl<'a> SsmlTag<'a> {
+    /// Parse the tag at the start of `input`, which begins with `<`
+    ///
+    /// Returns `None` for tags that aren't whitelisted or aren't well-formed.
+    fn parse(input: &'a str) -> Option<Self> {
+        let mut rest = input.strip_prefix('<')?;
+        let closing = rest.starts_with('/');
+        if closing {
+            rest = &rest[1..];
+        }
+
+        let name_len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(rest.len());
+        let name = &rest[..name_len];
+        if !SSML_TAGS.contains(&name) {
+            return None;
+        }
+        rest = &rest[name_len..];
+
+        let mut attributes = Vec::new();
+        loop {
+            let trimmed = rest.trim_start();
+            let separated = trimmed.len() < rest.len();
+            rest = trimmed;
+
+            let end = if rest.starts_with("/>") && !closing {
+                Some((2, true))
+            } else if rest.starts_with('>') {
+                Some((1, false))
+            } else {
+                None
+            };
+            if let Some((end_len, self_closing)) = end {
+                return Some(Self {
+                    name,
+                    attributes,
+                    closing,
+                    self_closing,
+                    len: input.len() - rest.len() + end_len,
+                });
+            }
+
+            if closing || !separated {
+                return None;
+            }
+
+            let (attr_name, value) = rest.split_once('=')?;
+            let valid_name = !attr_name.is_empty()
+                && attr_name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'));
+            if !valid_name {
+                return None;
+            }
+
+            let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+            let value = &value[1..];
+            let value_end = value.find(quote)?;
+            attributes.push((attr_name, &value[..value_end]));
+            rest = &value[value_end + 1..];
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        if self.closing {
+            return format!("</{}>", self.name);
+        }
+
+        let mut xml = format!("<{}", self.name);
+        for (name, value) in &self.attributes {
+            xml.push_str(&attr(name, &unescape_xml(value)));
+        }
+        xml.push_str(if self.self_closing { "/>" } else { ">" });
+        xml
+    }
+}
+
+/// Decode the standard XML entities, so values aren't escaped twice
+fn unescape_xml(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(c) = rest.chars().next() {
+        match XML_ENTITIES
+            .iter()
+            .find(|(entity, _)| rest.starts_with(entity))
+        {
+            Some((entity, decoded)) => {
+                unescaped.push(*decoded);
+                rest = &rest[entity.len()..];
+            }
+            None => {
+                unescaped.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    unescaped
+}
+
+/// Render a single escaped ` name="value"` attribute
+fn attr(name: &str, value: &str) -> String {
+    format!(r#" {}="{}""#, name, escape_xml(value))
+}
+
+/// Builder for the XML actions returned to AfricasTalking voice callbacks
+///
+/// All text and attribute values are XML-escaped.
+///
+/// ```rust
+/// use africastalking::voice::ActionBuilder;
+///
+/// let xml = ActionBuilder::new()
+///     .play("https://example.com/a.mp3?b=1&c=2")
+///     .build();
+///
+/// assert!(xml.contains(r#"<Play url="https://example.com/a.mp3?b=1&amp;c=2"/>"#));
+/// ```
+#[derive(Debug, Default)]
+pub struct ActionBuilder {
+    actions: Vec<String>,
+    /// First validation error from an added action, see `try_build`
+    error: Option<AfricasTalkingError>,
+}
+
+impl ActionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read out text to the caller
+    pub fn say(self, text: &str) -> Self {
+        self.say_with(text, SayAttributes::default())
+    }
+
+    /// Read out text to the caller with voice attributes
+    pub fn say_with(mut self, text: &str, attributes: SayAttributes) -> Self {
+        self.actions.push(attributes.to_xml(text));
+        self
+    }
+
+    /// Read out SSML-tagged text, e.g. with `<break time="500ms"/>` pauses
+    ///
+    /// Only the `break`, `emphasis`, `prosody`, `say-as`, `p`, `s` and `sub`
+    /// tags are kept; any other markup and stray `&` are escaped. Attribute
+    /// values are re-escaped, closing tags that don't match are escaped as
+    /// text, and tags left open are closed at the end.
+    ///
+    /// ```rust
+    /// use africastalking::voice::ActionBuilder;
+    ///
+    /// let xml = ActionBuilder::new()
+    ///     .say_ssml(r#"Welcome<break time="500ms"/>Press 1 for M&M <b>now</b>"#)
+    ///     .build();
+    /// assert!(xml.contains(
+    ///     r#"<Say>Welcome<break time="500ms"/>Press 1 for M&amp;M &lt;b&gt;now&lt;/b&gt;</Say>"#
+    /// ));
+    /// ```
+    pub fn say_ssml(mut self, ssml: &str) -> Self {
+        self.actions
+            .push(SayAttributes::default().to_xml_escaped(&escape_ssml(ssml)));
+        self
+    }
+
+    /// Play an audio file hosted at the given URL
+    pub fn play(mut self, url: &str) -> Self {
+        self.actions.push(format!("<Play{}/>", attr("url", url)));
+        self
+    }
+
+    /// Play several audio files one after another
+    ///
+    /// ```rust
+    /// use africastalking::voice::ActionBuilder;
+    ///
+    /// let xml = ActionBuilder::new()
+    ///     .play_all(vec!["https://a.mp3", "https://b.mp3", "https://c.mp3"])
+    ///     .build();
+    /// assert!(xml.contains(
+    ///     r#"<Play url="https://a.mp3"/><Play url="https://b.mp3"/><Play url="https://c.mp3"/>"#
+    /// ));
+    /// ```
+    pub fn play_all<S: Into<String>>(self, urls: Vec<S>) -> Self {
+        urls.into_iter()
+            .fold(self, |builder, url| builder.play(&url.into()))
+    }
+
+    /// Collect digits the caller enters on the keypad
+    ///
+    /// The action is checked with [`GetDigitsAction::validate`]; use
+    /// [`try_build`](Self::try_build) to see the error.
+    pub fn get_digits(mut self, action: GetDigitsAction) -> Self {
+        self.check(action.validate());
+        self.actions.push(action.to_xml());
+        self
+    }
+
+    /// Connect the caller to one or more phone numbers
+    ///
+    /// The action is checked with [`DialAction::validate`]; use
+    /// [`try_build`](Self::try_build) to see the error.
+    pub fn dial(mut self, action: DialAction) -> Self {
+        self.check(action.validate());
+        self.actions.push(action.to_xml());
+        self
+    }
+
+    /// Forward the call to another number without recording it
+    ///
+    /// `caller_id` is sent as the `callerId` of the forwarded leg. Pass the
+    /// `destinationNumber` from the [`VoiceNotification`] to keep showing the
+    /// number the caller dialled; it must be one of your AfricasTalking
+    /// numbers. Use [`dial`](Self::dial) for anything more specific.
+    ///
+    /// ```rust
+    /// use africastalking::voice::ActionBuilder;
+    ///
+    /// let xml = ActionBuilder::new()
+    ///     .transfer_to("+254711000000", "+254711082000")
+    ///     .build();
+    /// assert!(xml.contains(
+    ///     r#"<Dial phoneNumbers="+254711000000" record="false" callerId="+254711082000"/>"#
+    /// ));
+    /// ```
+    pub fn transfer_to(self, phone_number: &str, caller_id: &str) -> Self {
+        self.dial(
+            DialAction::new(vec![phone_number])
+                .record(false)
+                .caller_id(caller_id),
+        )
+    }
+
+    /// Keep the first validation error for `try_build`
+    fn check(&mut self, result: Result<()>) {
+        if let Err(e) = result {
+            self.error.get_or_insert(e);
+        }
+    }
+
+    /// Record the caller or the whole call
+    pub fn record(mut self, action: RecordAction) -> Self {
+        self.actions.push(action.to_xml());
+        self
+    }
+
+    /// Place the caller in a queue
+    pub fn enqueue(mut self, attributes: EnqueueAttributes) -> Self {
+        self.actions.push(attributes.to_xml());
+        self
+    }
+
+    /// Pass the next caller in a queue to an agent
+    pub fn dequeue(mut self, phone_number: &str, name: Option<&str>) -> Self {
+        let mut xml = format!("<Dequeue{}", attr("phoneNumber", phone_number));
+        if let Some(name) = name {
+            xml.push_str(&attr("name", name));
+        }
+        xml.push_str("/>");
+        self.actions.push(xml);
+        self
+    }
+
+    /// Join the caller to a conference
+    pub fn conference(self) -> Self {
+        self.conference_with(ConferenceAttributes::default())
+    }
+
+    /// Join the caller to a conference with the given attributes
+    pub fn conference_with(mut self, attributes: ConferenceAttributes) -> Self {
+        self.actions.push(attributes.to_xml());
+        self
+    }
+
+    /// Hand the call over to another callback URL
+    ///
+    /// ```rust
+    /// use africastalking::voice::ActionBuilder;
+    ///
+    /// let xml = ActionBuilder::new().redirect("https://example.com/next?a=1&b=2").build();
+    /// assert!(xml.contains("<Redirect>https://example.com/next?a=1&amp;b=2</Redirect>"));
+    /// ```
+    pub fn redirect(mut self, url: &str) -> Self {
+        self.actions
+            .push(format!("<Redirect>{}</Redirect>", escape_xml(url)));
+        self
+    }
+
+    /// Reject an incoming call without answering it
+    pub fn reject(mut self) -> Self {
+        self.actions.push("<Reject/>".to_string());
+        self
+    }
+
+    /// End the call once the preceding actions have run
+    ///
+    /// ```rust
+    /// use africastalking::voice::ActionBuilder;
+    ///
+    /// let xml = ActionBuilder::new().say("Goodbye").hangup().build();
+    /// assert!(xml.contains("<Say>Goodbye</Say><Hangup/>"));
+    /// ```
+    pub fn hangup(mut self) -> Self {
+        self.actions.push("<Hangup/>".to_string());
+        self
+    }
+
+    /// Build the final XML response, failing if an action was invalid
+    ///
+    /// ```rust
+    /// use africastalking::voice::{ActionBuilder, GetDigitsAction};
+    ///
+    /// let valid = GetDigitsAction::new().say("Enter your PIN").num_digits(4).timeout(30);
+    /// assert!(ActionBuilder::new().get_digits(valid).try_build().is_ok());
+    ///
+    /// let no_digits = GetDigitsAction::new().num_digits(0);
+    /// assert!(ActionBuilder::new().get_digits(no_digits).try_build().is_err());
+    ///
+    /// let no_timeout = GetDigitsAction::new().timeout(0);
+    /// assert!(ActionBuilder::new().get_digits(no_timeout).try_build().is_err());
+    /// ```
+    pub fn try_build(self) -> Result<String> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.build()),
+        }
+    }
+
+    /// Build the final XML response
+    ///
+    /// Actions are rendered even if they failed validation.
+    pub fn build(self) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><Response>{}</Response>"#,
+            self.actions.concat()
+        )
+    }
+}
+
+/// XML response for a voice callback
+///
+/// With the `axum` feature this implements `IntoResponse`, setting the
+/// `application/xml` content type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoiceResponse(pub String);
+
+impl VoiceResponse {
+    pub fn from_builder(builder: ActionBuilder) -> Self {
+        Self(builder.build())
+    }
+
+    /// The XML body
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<ActionBuilder> for VoiceResponse {
+    fn from(builder: ActionBuilder) -> Self {
+        Self::from_builder(builder)
+    }
+}
+
+#[cfg(feature = "axum")]
+impl axum::response::IntoResponse for VoiceResponse {
+    fn into_response(self) -> axum::response::Response {
+        (
+            [(axum::http::header::CONTENT_TYPE, "application/xml")],
+            self.0,
+        )
+            .into_response()
+    }
+}
+
+/// Voice used to read out `Say` text
+///
+/// ```rust
+/// use africastalking::voice::{ActionBuilder, SayAttributes, Voice};
+///
+/// let attributes = SayAttributes {
+///     voice: Some(Voice::Woman),
+///     ..Default::default()
+/// };
+/// let xml = ActionBuilder::new().say_with("Hello", attributes).build();
+/// assert!(xml.contains(r#"<Say voice="woman">Hello</Say>"#));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Voice {
+    Man,
+    Woman,
+    /// A voice not known to this version of the SDK
+    Other(String),
+}
+
+impl fmt::Display for Voice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Voice::Man => f.write_str("man"),
+            Voice::Woman => f.write_str("woman"),
+            Voice::Other(voice) => f.write_str(voice),
+        }
+    }
+}
+
+impl From<&str> for Voice {
+    fn from(voice: &str) -> Self {
+        match voice {
+            "man" => Voice::Man,
+            "woman" => Voice::Woman,
+            other => Voice::Other(other.to_string()),
+        }
+    }
+}
+
+/// Optional attributes for the `Say` action
+#[derive(Debug, Clone, Default)]
+pub struct SayAttributes {
+    /// Voice used to read the text
+    pub voice: Option<Voice>,
+    /// Play a beep after reading the text
+    pub play_beep: Option<bool>,
+}
+
+impl SayAttributes {
+    fn to_xml(&self, text: &str) -> String {
+        self.to_xml_escaped(&escape_xml(text))
+    }
+
+    /// Render a `Say` element around already escaped content
+    fn to_xml_escaped(&self, content: &str) -> String {
+        let mut xml = String::from("<Say");
+        if let Some(voice) = &self.voice {
+            xml.push_str(&attr("voice", &voice.to_string()));
+        }
+        if let Some(play_beep) = self.play_beep {
+            xml.push_str(&attr("playBeep", &play_beep.to_string()));
+        }
+        xml.push_str(&format!(">{content}</Say>"));
+        xml
+    }
+}
+
+/// A prompt played inside `GetDigits`
+#[derive(Debug, Clone)]
+pub enum Prompt {
+    Say(String, SayAttributes),
+    Play(String),
+}
+
+impl Prompt {
+    fn to_xml(&self) -> String {
+        match self {
+            Prompt::Say(text, attributes) => attributes.to_xml(text),
+            Prompt::Play(url) => format!("<Play{}/>", attr("url", url)),
+        }
+    }
+}
+
+/// The `GetDigits` action
+#[derive(Debug, Clone, Default)]
+pub struct GetDigitsAction {
+    /// Prompts played in order while waiting for input
+    pub prompts: Vec<Prompt>,
+    pub finish_on_key: Option<String>,
+    pub num_digits: Option<u32>,
+    pub timeout: Option<u32>,
+    pub callback_url: Option<String>,
+}
+
+impl GetDigitsAction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a `Say` prompt after any existing prompts
+    pub fn say<S: Into<String>>(self, text: S) -> Self {
+        self.say_with(text, SayAttributes::default())
+    }
+
+    /// Add a `Say` prompt with voice attributes after any existing prompts
+    pub fn say_with<S: Into<String>>(mut self, text: S, attributes: SayAttributes) -> Self {
+        self.prompts.push(Prompt::Say(text.into(), attributes));
+        self
+    }
+
+    /// Add a `Play` prompt after any existing prompts
+    pub fn play<S: Into<String>>(mut self, url: S) -> Self {
+        self.prompts.push(Prompt::Play(url.into()));
+        self
+    }
+
+    /// Add a `Play` prompt for each URL, in order
+    pub fn play_all<S: Into<String>>(mut self, urls: Vec<S>) -> Self {
+        self.prompts
+            .extend(urls.into_iter().map(|url| Prompt::Play(url.into())));
+        self
+    }
+
+    pub fn finish_on_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.finish_on_key = Some(key.into());
+        self
+    }
+
+    pub fn num_digits(mut self, num_digits: u32) -> Self {
+        self.num_digits = Some(num_digits);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: u32) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn callback_url<S: Into<String>>(mut self, url: S) -> Self {
+        self.callback_url = Some(url.into());
+        self
+    }
+
+    /// Check that `num_digits` and `timeout` are at least 1 when set
+    pub fn validate(&self) -> Result<()> {
+        if self.num_digits == Some(0) {
+            return Err(AfricasTalkingError::validation(
+                "GetDigits numDigits must be at least 1",
+            ));
+        }
+        if self.timeout == Some(0) {
+            return Err(AfricasTalkingError::validation(
+                "GetDigits timeout must be at least 1 second",
+            ));
+        }
+        Ok(())
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = String::from("<GetDigits");
+        if let Some(timeout) = self.timeout {
+            xml.push_str(&attr("timeout", &timeout.to_string()));
+        }
+        if let Some(key) = &self.finish_on_key {
+            xml.push_str(&attr("finishOnKey", key));
+        }
+        if let Some(num_digits) = self.num_digits {
+            xml.push_str(&attr("numDigits", &num_digits.to_string()));
+        }
+        if let Some(url) = &self.callback_url {
+            xml.push_str(&attr("callbackUrl", url));
+        }
+        xml.push('>');
+        for prompt in &self.prompts {
+            xml.push_str(&prompt.to_xml());
+        }
+        xml.push_str("</GetDigits>");
+        xml
+    }
+}
+
+/// The `Dial` action
+#[derive(Debug, Clone, Default)]
+pub struct DialAction {
+    pub phone_numbers: Vec<String>,
+    /// SIP addresses such as `agent@ke.sip.africastalking.com`
+    pub sip_addresses: Vec<String>,
+    pub record: Option<bool>,
+    pub sequential: Option<bool>,
+    pub caller_id: Option<String>,
+    pub ring_back_tone: Option<String>,
+    pub max_duration: Option<u32>,
+    /// URL the recording is posted to, used only when `record` is true
+    pub recording_callback_url: Option<String>,
+}
+
+impl DialAction {
+    pub fn new<S: Into<String>>(phone_numbers: Vec<S>) -> Self {
+        Self {
+            phone_numbers: phone_numbers.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Dial SIP addresses only
+    pub fn sip<S: Into<String>>(addresses: Vec<S>) -> Self {
+        Self::default().with_sip(addresses)
+    }
+
+    /// Also dial these SIP addresses, after any phone numbers
+    ///
+    /// ```rust
+    /// use africastalking::voice::{ActionBuilder, DialAction};
+    ///
+    /// let dial = DialAction::new(vec!["+254711000000"]).with_sip(vec!["sip:agent@ke.sip.africastalking.com"]);
+    /// assert!(ActionBuilder::new().dial(dial).build().contains(
+    ///     r#"<Dial phoneNumbers="+254711000000,agent@ke.sip.africastalking.com"/>"#
+    /// ));
+    /// ```
+    pub fn with_sip<S: Into<String>>(mut self, addresses: Vec<S>) -> Self {
+        self.sip_addresses
+            .extend(addresses.into_iter().map(|address| {
+                let address = address.into();
+                match address.strip_prefix("sip:") {
+                    Some(stripped) => stripped.to_string(),
+                    None => address,
+                }
+            }));
+        self
+    }
+
+    /// Check that there is at least one number or SIP address to dial, and
+    /// that a recording callback is only set on recorded dials
+    pub fn validate(&self) -> Result<()> {
+        if self.targets().next().is_none() {
+            return Err(AfricasTalkingError::validation(
+                "Dial needs at least one phone number or SIP address",
+            ));
+        }
+        if self.recording_callback_url.is_some() && self.record != Some(true) {
+            return Err(AfricasTalkingError::validation(
+                "A recording callback URL needs `record(true)`",
+            ));
+        }
+        Ok(())
+    }
+
+    fn targets(&self) -> impl Iterator<Item = &str> {
+        self.phone_numbers
+            .iter()
+            .chain(&self.sip_addresses)
+            .map(String::as_str)
+            .filter(|target| !target.trim().is_empty())
+    }
+
+    pub fn record(mut self, record: bool) -> Self {
+        self.record = Some(record);
+        self
+    }
+
+    pub fn sequential(mut self, sequential: bool) -> Self {
+        self.sequential = Some(sequential);
+        self
+    }
+
+    pub fn caller_id<S: Into<String>>(mut self, caller_id: S) -> Self {
+        self.caller_id = Some(caller_id.into());
+        self
+    }
+
+    pub fn ring_back_tone<S: Into<String>>(mut self, url: S) -> Self {
+        self.ring_back_tone = Some(url.into());
+        self
+    }
+
+    pub fn max_duration(mut self, seconds: u32) -> Self {
+        self.max_duration = Some(seconds);
+        self
+    }
+
+    /// Post the recording of a recorded dial to this URL
+    ///
+    /// ```rust
+    /// use africastalking::voice::{ActionBuilder, DialAction};
+    ///
+    /// let dial = DialAction::new(vec!["+254711000000"])
+    ///     .record(true)
+    ///     .recording_callback_url("https://example.com/recordings?a=1&b=2");
+    /// let xml = ActionBuilder::new().dial(dial).build();
+    /// assert!(xml.contains(r#"record="true""#));
+    /// assert!(xml.contains(r#"recordingCallback="https://example.com/recordings?a=1&amp;b=2""#));
+    /// ```
+    pub fn recording_callback_url<S: Into<String>>(mut self, url: S) -> Self {
+        self.recording_callback_url = Some(url.into());
+        self
+    }
+
+    fn to_xml(&self) -> String {
+        let targets = self.targets().collect::<Vec<_>>().join(",");
+        let mut xml = format!("<Dial{}", attr("phoneNumbers", &targets));
+        if let Some(record) = self.record {
+            xml.push_str(&attr("record", &record.to_string()));
+        }
+        if let Some(sequential) = self.sequential {
+            xml.push_str(&attr("sequential", &sequential.to_string()));
+        }
+        if let Some(caller_id) = &self.caller_id {
+            xml.push_str(&attr("callerId", caller_id));
+        }
+        if let Some(url) = &self.ring_back_tone {
+            xml.push_str(&attr("ringbackTone", url));
+        }
+        if let Some(max_duration) = self.max_duration {
+            xml.push_str(&attr("maxDuration", &max_duration.to_string()));
+        }
+        if self.record == Some(true)
+            && let Some(url) = &self.recording_callback_url
+        {
+            xml.push_str(&attr("recordingCallback", url));
+        }
+        xml.push_str("/>");
+        xml
+    }
+}
+
+/// The `Record` action
+#[derive(Debug, Clone, Default)]
+pub struct RecordAction {
+    pub say: Option<String>,
+    /// Audio files played in order before recording starts
+    pub play_urls: Vec<String>,
+    pub finish_on_key: Option<String>,
+    pub max_length: Option<u32>,
+    pub timeout: Option<u32>,
+    pub trim_silence: Option<bool>,
+    pub play_beep: Option<bool>,
+    pub callback_url: Option<String>,
+}
+
+impl RecordAction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn say<S: Into<String>>(mut self, text: S) -> Self {
+        self.say = Some(text.into());
+        self
+    }
+
+    pub fn play<S: Into<String>>(mut self, url: S) -> Self {
+        self.play_urls.push(url.into());
+        self
+    }
+
+    pub fn play_all<S: Into<String>>(mut self, urls: Vec<S>) -> Self {
+        self.play_urls.extend(urls.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn finish_on_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.finish_on_key = Some(key.into());
+        self
+    }
+
+    pub fn max_length(mut self, seconds: u32) -> Self {
+        self.max_length = Some(seconds);
+        self
+    }
+
+    pub fn timeout(mut self, seconds: u32) -> Self {
+        self.timeout = Some(seconds);
+        self
+    }
+
+    pub fn trim_silence(mut self, trim_silence: bool) -> Self {
+        self.trim_silence = Some(trim_silence);
+        self
+    }
+
+    pub fn play_beep(mut self, play_beep: bool) -> Self {
+        self.play_beep = Some(play_beep);
+        self
+    }
+
+    pub fn callback_url<S: Into<String>>(mut self, url: S) -> Self {
+        self.callback_url = Some(url.into());
+        self
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = String::from("<Record");
+        if let Some(key) = &self.finish_on_key {
+            xml.push_str(&attr("finishOnKey", key));
+        }
+        if let Some(max_length) = self.max_length {
+            xml.push_str(&attr("maxLength", &max_length.to_string()));
+        }
+        if let Some(timeout) = self.timeout {
+            xml.push_str(&attr("timeout", &timeout.to_string()));
+        }
+        if let Some(trim_silence) = self.trim_silence {
+            xml.push_str(&attr("trimSilence", &trim_silence.to_string()));
+        }
+        if let Some(play_beep) = self.play_beep {
+            xml.push_str(&attr("playBeep", &play_beep.to_string()));
+        }
+        if let Some(url) = &self.callback_url {
+            xml.push_str(&attr("callbackUrl", url));
+        }
+
+        // Without a prompt this records the whole call
+        if self.say.is_none() && self.play_urls.is_empty() {
+            xml.push_str("/>");
+            return xml;
+        }
+
+        xml.push('>');
+        if let Some(text) = &self.say {
+            xml.push_str(&SayAttributes::default().to_xml(text));
+        }
+        for url in &self.play_urls {
+            xml.push_str(&format!("<Play{}/>", attr("url", url)));
+        }
+        xml.push_str("</Record>");
+        xml
+    }
+}
+
+/// Attributes for the `Enqueue` action
+#[derive(Debug, Clone, Default)]
+pub struct EnqueueAttributes {
+    /// Name of the queue to place the caller in
+    pub name: Option<String>,
+    /// URL of the audio played while the caller waits
+    pub hold_music: Option<String>,
+}
+
+impl EnqueueAttributes {
+    fn to_xml(&self) -> String {
+        let mut xml = String::from("<Enqueue");
+        if let Some(url) = &self.hold_music {
+            xml.push_str(&attr("holdMusic", url));
+        }
+        if let Some(name) = &self.name {
+            xml.push_str(&attr("name", name));
+        }
+        xml.push_str("/>");
+        xml
+    }
+}
+
+/// Attributes for the `Conference` action
+#[derive(Debug, Clone, Default)]
+pub struct ConferenceAttributes {
+    /// Name of the conference to join
+    pub name: Option<String>,
+    /// Hold callers in a waiting room until the conference starts
+    pub waiting_room: Option<bool>,
+    /// URL of the audio played while callers wait
+    pub hold_music: Option<String>,
+    /// Maximum number of participants
+    pub max_participants: Option<u32>,
+}
+
+impl ConferenceAttributes {
+    fn to_xml(&self) -> String {
+        let mut xml = String::from("<Conference");
+        if let Some(name) = &self.name {
+            xml.push_str(&attr("name", name));
+        }
+        if let Some(waiting_room) = self.waiting_room {
+            xml.push_str(&attr("waitingRoom", &waiting_room.to_string()));
+        }
+        if let Some(url) = &self.hold_music {
+            xml.push_str(&attr("holdMusic", url));
+        }
+        if let Some(max_participants) = self.max_participants {
+            xml.push_str(&attr("maxParticipants", &max_participants.to_string()));
+        }
+        xml.push_str("/>");
+        xml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockAfricasTalking;
+    use wiremock::{
+        Mock, ResponseTemplate,
+        matchers::{body_partial_json, method, path},
+    };
+
+    #[cfg(feature = "media-upload")]
+    fn temp_audio_file() -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}.mp3", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"ID3 fake audio").unwrap();
+        path
+    }
+
+    /// Client for a mock that answers `endpoint` only when the JSON body
+    /// carries the configured username
+    async fn expect_username(endpoint: &str, body: serde_json::Value) -> MockAfricasTalking {
+        let mock = MockAfricasTalking::start().await;
+        Mock::given(method("POST"))
+            .and(path(endpoint))
+            .and(body_partial_json(
+                serde_json::json!({ "username": "my_app" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .expect(1)
+            .mount(mock.server())
+            .await;
+        mock
+    }
+
+    fn client_for(mock: &MockAfricasTalking) -> AfricasTalkingClient {
+        let config = crate::Config::new("test-api-key", "my_app")
+            .base_url(mock.server().uri())
+            .max_retries(0);
+        AfricasTalkingClient::new(config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn make_call_sends_the_configured_username() {
+        let mock = expect_username("/call", serde_json::json!({ "entries": [] })).await;
+        let request = MakeCallRequest::new("+254711082000", vec!["+254711082001"]);
+
+        client_for(&mock).voice().make_call(request).await.unwrap();
+        mock.server().verify().await;
+    }
+
+    #[tokio::test]
+    async fn get_queued_calls_sends_the_configured_username() {
+        let mock =
+            expect_username("/queueStatus", serde_json::json!({ "status": "Success" })).await;
+        let request = QueueStatusRequest::new(vec!["+254711082000"]);
+
+        client_for(&mock)
+            .voice()
+            .get_queued_calls(request)
+            .await
+            .unwrap();
+        mock.server().verify().await;
+    }
+
+    #[tokio::test]
+    async fn upload_media_sends_the_configured_username() {
+        let mock = expect_username("/mediaUpload", serde_json::json!({})).await;
+        let request = UploadMediaRequest::new("https://example.com/hold.mp3", "+254711082000");
+
+        client_for(&mock)
+            .voice()
+            .upload_media(request)
+            .await
+            .unwrap();
+        mock.server().verify().await;
+    }
+
+    #[cfg(feature = "media-upload")]
+    #[tokio::test]
+    async fn upload_media_file_sends_file_part_and_username() {
+        let mock = MockAfricasTalking::start().await;
+        Mock::given(method("POST"))
+            .and(path("/mediaUpload"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(mock.server())
+            .await;
+        mock.mock_json("POST", "/mediaUpload", 200, serde_json::json!({}))
+            .await;
+        let config = mock
+            .config()
+            .max_retries(1)
+            .retry_base_delay(std::time::Duration::from_millis(1));
+        let client = AfricasTalkingClient::new(config).unwrap();
+
+        let file = temp_audio_file();
+        let response = client
+            .voice()
+            .upload_media_file(&file, "+254711082000")
+            .await;
+        let file_name = file.file_name().unwrap().to_string_lossy().into_owned();
+        std::fs::remove_file(&file).unwrap();
+        assert!(response.unwrap().error_message.is_none());
+
+        // The retried request carries the same form
+        let requests = mock.server().received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        for request in requests {
+            let content_type = request.headers["content-type"].to_str().unwrap();
+            assert!(content_type.starts_with("multipart/form-data; boundary="));
+
+            let body = String::from_utf8_lossy(&request.body);
+            assert!(body.contains("name=\"username\"\r\n\r\nsandbox\r\n"));
+            assert!(body.contains("name=\"phoneNumber\"\r\n\r\n+254711082000\r\n"));
+            assert!(body.contains(&format!(
+                "name=\"{MEDIA_FILE_FIELD}\"; filename=\"{file_name}\"\r\nContent-Type: audio/mpeg\r\n\r\nID3 fake audio\r\n"
+            )));
+        }
+    }
+
+    #[cfg(feature = "media-upload")]
+    #[tokio::test]
+    async fn upload_media_file_rejects_missing_files() {
+        let mock = MockAfricasTalking::start().await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let missing = std::env::temp_dir().join(format!("{}.wav", uuid::Uuid::new_v4()));
+        let error = client
+            .voice()
+            .upload_media_file(&missing, "+254711082000")
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AfricasTalkingError::Validation(_)));
+        assert!(mock.server().received_requests().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn make_call_new_checked_normalizes_both_numbers_in_the_payload() {
+        let request =
+            MakeCallRequest::new_checked("0711082000", vec!["0712345678", "+14155550100"], "KE")
+                .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["from"], "+254711082000");
+        assert_eq!(json["to"], "+254712345678,+14155550100");
+    }
+
+    #[test]
+    fn call_status_reads_each_documented_status() {
+        let response: MakeCallResponse = serde_json::from_value(serde_json::json!({
+            "entries": [
+                { "phoneNumber": "+254711082000", "status": "Queued", "sessionId": "ATVId_1" },
+                { "phoneNumber": "+254711082001", "status": "InvalidPhoneNumber" },
+                { "phoneNumber": "+254711082002", "status": "DestinationNotSupported" },
+                { "phoneNumber": "+254711082003", "status": "InsufficientCredit" },
+                { "phoneNumber": "+254711082004", "status": "Invalid Phone Number" },
+                { "phoneNumber": "+254711082005", "status": "Ringing" }
+            ],
+            "errorMessage": "None"
+        }))
+        .unwrap();
+
+        let statuses: Vec<_> = response.entries.iter().map(|entry| &entry.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                &CallStatus::Queued,
+                &CallStatus::InvalidPhoneNumber,
+                &CallStatus::DestinationNotSupported,
+                &CallStatus::InsufficientCredit,
+                &CallStatus::InvalidPhoneNumber,
+                &CallStatus::Unknown("Ringing".into()),
+            ]
+        );
+        assert!(statuses[0].is_queued());
+        assert!(statuses[1..].iter().all(|status| !status.is_queued()));
+        assert_eq!(response.entries[0].session_id.as_deref(), Some("ATVId_1"));
+
+        for status in &statuses[..4] {
+            assert_eq!(&CallStatus::from(status.to_string()), *status);
+        }
+        assert_eq!(statuses[5].to_string(), "Ringing");
+    }
+
+    #[test]
+    fn transfer_to_keeps_caller_id_and_skips_recording() {
+        let notification: VoiceNotification = serde_json::from_value(serde_json::json!({
+            "sessionId": "ATVId_1",
+            "callerNumber": "+254712345678",
+            "destinationNumber": "+254711082000"
+        }))
+        .unwrap();
+        let caller_id = notification.destination_number.as_deref().unwrap();
+
+        let xml = ActionBuilder::new()
+            .transfer_to("+254722000000", caller_id)
+            .try_build()
+            .unwrap();
+        assert_eq!(
+            xml,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Response><Dial phoneNumbers=\"+254722000000\" record=\"false\" callerId=\"+254711082000\"/></Response>"
+        );
+    }
+
+    #[test]
+    fn make_call_new_checked_rejects_malformed_numbers() {
+        assert!(MakeCallRequest::new_checked("+254711082000", vec!["07l2345678"], "KE").is_err());
+        assert!(MakeCallRequest::new_checked("", vec!["0712345678"], "KE").is_err());
+    }
+
+    #[test]
+    fn say_ssml_keeps_breaks_and_escapes_stray_ampersands() {
+        let xml = ActionBuilder::new()
+            .say_ssml(r#"Tom & Jerry<break time="500ms"/>&amp; friends"#)
+            .build();
+        assert!(xml.contains(r#"<Say>Tom &amp; Jerry<break time="500ms"/>&amp; friends</Say>"#));
+    }
+
+    #[test]
+    fn say_ssml_escapes_unknown_tags() {
+        let xml = ActionBuilder::new()
+            .say_ssml("<b>bold</b> <script>")
+            .build();
+        assert!(xml.contains("<Say>&lt;b&gt;bold&lt;/b&gt; &lt;script&gt;</Say>"));
+    }
+
+    #[test]
+    fn say_ssml_escapes_attribute_values() {
+        let xml = ActionBuilder::new()
+            .say_ssml(r#"<prosody rate="a&b">Hi</prosody><break time="1s" x="<"/>"#)
+            .build();
+        assert!(xml.contains(
+            r#"<Say><prosody rate="a&amp;b">Hi</prosody><break time="1s" x="&lt;"/></Say>"#
+        ));
+
+        let xml = ActionBuilder::new()
+            .say_ssml(r#"<prosody rate="a&amp;b">Hi</prosody>"#)
+            .build();
+        assert!(xml.contains(r#"<prosody rate="a&amp;b">"#));
+    }
+
+    #[test]
+    fn say_ssml_keeps_the_output_well_formed() {
+        let xml = ActionBuilder::new()
+            .say_ssml("<emphasis><prosody>Hi</emphasis></prosody>")
+            .build();
+        assert!(
+            xml.contains("<Say><emphasis><prosody>Hi&lt;/emphasis&gt;</prosody></emphasis></Say>")
+        );
+
+        let xml = ActionBuilder::new().say_ssml("</p>Hi<s>there").build();
+        assert!(xml.contains("<Say>&lt;/p&gt;Hi<s>there</s></Say>"));
+    }
+
+    #[test]
+    fn say_ssml_escapes_malformed_tags() {
+        let xml = ActionBuilder::new()
+            .say_ssml(r#"<break time=500ms/><break time="1s"<p>"#)
+            .build();
+        assert!(xml.contains(r#"<Say>&lt;break time=500ms/&gt;&lt;break time="1s"<p></p></Say>"#));
+    }
+
+    #[test]
+    fn conference_with_renders_every_attribute() {
+        let attributes = ConferenceAttributes {
+            name: Some("Sales & Support".into()),
+            waiting_room: Some(true),
+            hold_music: Some("https://example.com/hold.mp3".into()),
+            max_participants: Some(10),
+        };
+        let xml = ActionBuilder::new().conference_with(attributes).build();
+        assert!(xml.contains(
+            r#"<Conference name="Sales &amp; Support" waitingRoom="true" holdMusic="https://example.com/hold.mp3" maxParticipants="10"/>"#
+        ));
+
+        let xml = ActionBuilder::new().conference().build();
+        assert!(xml.contains("<Response><Conference/></Response>"));
+    }
+
+    #[test]
+    fn get_digits_renders_prompts_in_insertion_order() {
+        let action = GetDigitsAction::new()
+            .play("https://example.com/welcome.mp3")
+            .say("Enter your PIN")
+            .play_all(vec![
+                "https://example.com/a.mp3",
+                "https://example.com/b.mp3",
+            ])
+            .say("then press hash")
+            .num_digits(4);
+        let xml = ActionBuilder::new().get_digits(action).build();
+        assert!(xml.contains(concat!(
+            r#"<GetDigits numDigits="4">"#,
+            r#"<Play url="https://example.com/welcome.mp3"/>"#,
+            "<Say>Enter your PIN</Say>",
+            r#"<Play url="https://example.com/a.mp3"/>"#,
+            r#"<Play url="https://example.com/b.mp3"/>"#,
+            "<Say>then press hash</Say>",
+            "</GetDigits>",
+        )));
+    }
+
+    #[cfg(feature = "axum")]
+    #[tokio::test]
+    async fn voice_response_is_served_as_xml() {
+        use axum::response::IntoResponse;
+
+        let response = VoiceResponse::from(ActionBuilder::new().say("Hello")).into_response();
+        assert_eq!(response.headers()["content-type"], "application/xml");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            body,
+            r#"<?xml version="1.0" encoding="UTF-8"?><Response><Say>Hello</Say></Response>"#
+        );
+    }
+
+    #[test]
+    fn voice_notification_reads_a_completed_call() {
+        let notification: VoiceNotification = serde_json::from_value(serde_json::json!({
+            "sessionId": "ATVId_5e7d1a2b3c4d",
+            "isActive": "0",
+            "direction": "Inbound",
+            "callerNumber": "+254711082000",
+            "destinationNumber": "+254711082999",
+            "status": "Success",
+            "callSessionState": "Completed",
+            "durationInSeconds": 42,
+            "currencyCode": "KES",
+            "amount": "1.2000",
+            "recordingUrl": "https://voice.africastalking.com/recordings/ATVId_5e7d1a2b3c4d.mp3",
+            "hangupCause": "NORMAL_CLEARING",
+            "callStartTime": "2024-03-14 09:41:22"
+        }))
+        .unwrap();
+        assert!(!notification.is_active());
+        assert_eq!(notification.status, Some(VoiceCallStatus::Success));
+        assert_eq!(notification.duration_in_seconds, Some(42));
+        assert_eq!(
+            notification.recording_url.as_deref(),
+            Some("https://voice.africastalking.com/recordings/ATVId_5e7d1a2b3c4d.mp3")
+        );
+    }
+
+    #[test]
+    fn queue_status_reads_a_bare_total() {
+        let response: QueueStatusResponse =
+            serde_json::from_value(serde_json::json!({ "NumQueuedCalls": 3 })).unwrap();
+        assert!(response.entries.is_empty());
+        assert_eq!(response.total_queued_calls(), 3);
+
+        let response: QueueStatusResponse = serde_json::from_value(serde_json::json!({
+            "status": "Success",
+            "entries": [
+                { "phoneNumber": "+254711082000", "queueName": "sales", "numCalls": 2 },
+                { "phoneNumber": "+254711082001", "numCalls": 1 }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(response.total_queued_calls(), 3);
+    }
+
+    #[test]
+    fn make_call_validate_rejects_empty_and_malformed_recipients() {
+        let no_recipients: Vec<&str> = Vec::new();
+        for call_to in [
+            no_recipients,
+            vec![" , "],
+            vec!["0712345678"],
+            vec!["+254711082000", "254711082001"],
+            vec!["+0711082000"],
+            vec!["+2547"],
+        ] {
+            let request = MakeCallRequest::new("+254711082000", call_to.clone());
+            let error = request.validate().unwrap_err();
+            assert!(
+                matches!(error, AfricasTalkingError::Validation(_)),
+                "{call_to:?}"
+            );
+        }
+
+        let request = MakeCallRequest::new("+254711082000", vec!["+254711082001", "+14155550100"]);
+        assert!(request.validate().is_ok());
+    }
+
+    #[test]
+    fn make_call_new_checked_reports_validation_errors() {
+        for call_to in [vec!["07l2345678"], vec!["0712"], vec![""]] {
+            let error = MakeCallRequest::new_checked("+254711082000", call_to.clone(), "KE")
+                .and_then(|request| request.validate().map(|_| request))
+                .unwrap_err();
+            assert!(
+                matches!(error, AfricasTalkingError::Validation(_)),
+                "{call_to:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn make_call_validates_before_sending() {
+        let mock = MockAfricasTalking::start().await;
+        let client = AfricasTalkingClient::new(mock.config()).unwrap();
+
+        let request = MakeCallRequest::new("+254711082000", vec!["0712345678"]);
+        let error = client.voice().make_call(request).await.unwrap_err();
+        assert!(matches!(error, AfricasTalkingError::Validation(_)));
+        assert!(mock.server().received_requests().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn dial_renders_phone_numbers_only() {
+        let dial = DialAction::new(vec!["+254711082000", "+254711082001"]).sequential(true);
+        assert!(dial.validate().is_ok());
+        assert!(
+            ActionBuilder::new().dial(dial).build().contains(
+                r#"<Dial phoneNumbers="+254711082000,+254711082001" sequential="true"/>"#
+            )
+        );
+    }
+
+    #[test]
+    fn dial_renders_sip_addresses_without_the_scheme() {
+        let dial = DialAction::sip(vec![
+            "sip:agent@ke.sip.africastalking.com",
+            "support@ke.sip.africastalking.com",
+        ]);
+        assert!(dial.phone_numbers.is_empty());
+        assert_eq!(
+            dial.sip_addresses,
+            [
+                "agent@ke.sip.africastalking.com",
+                "support@ke.sip.africastalking.com"
+            ]
+        );
+        assert!(ActionBuilder::new().dial(dial).try_build().unwrap().contains(
+            r#"<Dial phoneNumbers="agent@ke.sip.africastalking.com,support@ke.sip.africastalking.com"/>"#
+        ));
+    }
+
+    #[test]
+    fn dial_validate_rejects_invalid_targets() {
+        let no_targets: Vec<&str> = Vec::new();
+        assert!(DialAction::new(no_targets.clone()).validate().is_err());
+        assert!(DialAction::sip(no_targets).validate().is_err());
+        assert!(DialAction::new(vec!["", "  "]).validate().is_err());
+
+        let unrecorded = DialAction::new(vec!["+254711082000"])
+            .recording_callback_url("https://example.com/recordings");
+        let error = unrecorded.validate().unwrap_err();
+        assert!(matches!(error, AfricasTalkingError::Validation(_)));
+
+        let no_targets = ActionBuilder::new().dial(DialAction::default());
+        assert!(no_targets.try_build().is_err());
+    }
+}
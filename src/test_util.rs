@@ -0,0 +1,106 @@
+//! A fake AfricasTalking API for integration tests
+//!
+//! Enabled with the `test-util` feature, and always in this crate's tests. [`MockAfricasTalking`] runs a local
+//! HTTP server and hands out a [`Config`] pointing every endpoint at it.
+
+use crate::config::Config;
+use serde_json::Value;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+/// Local server answering AfricasTalking requests with canned responses
+///
+/// ```rust
+/// use africastalking::{AfricasTalkingClient, sms::SendSmsRequest, test_util::MockAfricasTalking};
+/// use serde_json::json;
+///
+/// # #[tokio::main]
+/// # async fn main() -> africastalking::Result<()> {
+/// let mock = MockAfricasTalking::start().await;
+/// mock.send_sms(json!({
+///     "SMSMessageData": {
+///         "Message": "Sent to 1/1 Total Cost: KES 0.8000",
+///         "Recipients": [{
+///             "statusCode": 101,
+///             "number": "+254711000000",
+///             "status": "Success",
+///             "cost": "KES 0.8000",
+///             "messageId": "ATPid_1"
+///         }]
+///     }
+/// }))
+/// .await;
+///
+/// let client = AfricasTalkingClient::new(mock.config())?;
+/// let response = client
+///     .sms()
+///     .send(SendSmsRequest::new(vec!["+254711000000"], "Hello"))
+///     .await?;
+/// assert_eq!(response.sms_message_data.recipients.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MockAfricasTalking {
+    server: MockServer,
+}
+
+impl MockAfricasTalking {
+    /// Start a server on a random local port
+    pub async fn start() -> Self {
+        Self {
+            server: MockServer::start().await,
+        }
+    }
+
+    /// Sandbox config sending every request to this server, without retries
+    pub fn config(&self) -> Config {
+        Config::new("test-api-key", "sandbox")
+            .base_url(self.server.uri())
+            .max_retries(0)
+    }
+
+    /// The underlying server, for custom mocks or inspecting received requests
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+
+    /// Answer `http_method` requests to `endpoint` with a JSON body
+    pub async fn mock_json(&self, http_method: &str, endpoint: &str, status: u16, body: Value) {
+        Mock::given(method(http_method))
+            .and(path(endpoint))
+            .respond_with(ResponseTemplate::new(status).set_body_json(body))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answer `SmsModule::send`
+    pub async fn send_sms(&self, body: Value) {
+        self.mock_json("POST", "/version1/messaging", 201, body)
+            .await;
+    }
+
+    /// Answer `AirtimeModule::send`
+    pub async fn send_airtime(&self, body: Value) {
+        self.mock_json("POST", "/version1/airtime/send", 201, body)
+            .await;
+    }
+
+    /// Answer `DataModule::send`
+    pub async fn send_data(&self, body: Value) {
+        self.mock_json("POST", "/mobile/data/request", 201, body)
+            .await;
+    }
+
+    /// Answer `VoiceModule::make_call`
+    pub async fn make_call(&self, body: Value) {
+        self.mock_json("POST", "/call", 200, body).await;
+    }
+
+    /// Answer `ApplicationModule::get_data`
+    pub async fn application_data(&self, body: Value) {
+        self.mock_json("GET", "/version1/user", 200, body).await;
+    }
+}
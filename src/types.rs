@@ -1,14 +1,59 @@
 //! Common types used across the SDK
 
+use crate::error::{AfricasTalkingError, Result};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-/// Standard response wrapper for most API calls
+/// Envelope around responses that share the `status` and `errorMessage`
+/// fields
+///
+/// The envelope fields are taken out before the rest of the object is
+/// deserialized into `data`, so `T` should not declare them itself.
+///
+/// ```rust
+/// use africastalking::{ApiResponse, AtResponse};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Transfer {
+///     #[serde(rename = "transactionId")]
+///     transaction_id: String,
+/// }
+///
+/// let response: ApiResponse<Transfer> =
+///     serde_json::from_str(r#"{"status": "Success", "transactionId": "ATPid_1"}"#).unwrap();
+/// assert_eq!(response.status.as_deref(), Some("Success"));
+/// assert_eq!(response.into_data().unwrap().transaction_id, "ATPid_1");
+///
+/// let response: ApiResponse<serde_json::Value> =
+///     serde_json::from_str(r#"{"status": "Failed", "errorMessage": "Insufficient balance"}"#)
+///         .unwrap();
+/// assert!(response.is_error());
+/// ```
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ApiResponse<T> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(rename = "errorMessage", skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
     #[serde(flatten)]
     pub data: T,
 }
 
+impl<T> ApiResponse<T> {
+    /// The wrapped data, or `AfricasTalkingError::Api` if the envelope
+    /// carries an error
+    pub fn into_data(self) -> Result<T> {
+        self.into_result().map(|response| response.data)
+    }
+}
+
+impl<T> AtResponse for ApiResponse<T> {
+    fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+}
+
 /// Standard error response from the API
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ErrorResponse {
@@ -18,17 +63,73 @@ pub struct ErrorResponse {
     pub error_code: Option<String>,
 }
 
+/// Responses that report application errors in an `errorMessage` field
+///
+/// AfricasTalking returns some errors with HTTP 200, so these must be checked
+/// even when the request succeeded.
+///
+/// ```rust
+/// use africastalking::{AtResponse, voice::MakeCallResponse};
+///
+/// let ok: MakeCallResponse = serde_json::from_str(r#"{"entries": [], "errorMessage": "None"}"#).unwrap();
+/// assert!(!ok.is_error());
+///
+/// let failed: MakeCallResponse =
+///     serde_json::from_str(r#"{"entries": [], "errorMessage": "Invalid callerId"}"#).unwrap();
+/// assert!(failed.into_result().is_err());
+/// ```
+pub trait AtResponse: Sized {
+    /// The `errorMessage` field, if any
+    fn error_message(&self) -> Option<&str>;
+
+    /// Whether the response carries an error (`"None"` and empty messages
+    /// are treated as no error)
+    fn is_error(&self) -> bool {
+        self.error_message()
+            .is_some_and(|message| !message.is_empty() && message != "None")
+    }
+
+    /// Turn an error-bearing response into `AfricasTalkingError::Api`
+    fn into_result(self) -> Result<Self> {
+        if self.is_error() {
+            let message = self.error_message().unwrap_or_default().to_string();
+            return Err(AfricasTalkingError::api_error(
+                message,
+                "200 OK".to_string(),
+                None,
+            ));
+        }
+        Ok(self)
+    }
+}
+
 /// Pagination information for list responses
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Pagination {
     pub page: u32,
+    #[serde(rename = "perPage", alias = "per_page")]
     pub per_page: u32,
+    /// Total number of items across all pages
     pub total: u32,
+    /// Zero when the API leaves it out; the helpers use `total` instead
+    #[serde(rename = "totalPages", alias = "total_pages", default)]
     pub total_pages: u32,
 }
 
+impl Pagination {
+    /// Whether there are items after this page
+    pub fn has_next_page(&self) -> bool {
+        u64::from(self.page) * u64::from(self.per_page) < u64::from(self.total)
+    }
+
+    /// The number of the next page, if there is one
+    pub fn next_page(&self) -> Option<u32> {
+        self.has_next_page().then(|| self.page + 1)
+    }
+}
+
 /// Currency types supported by AfricasTalking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Currency {
     #[serde(rename = "KES")]
     Kes,
@@ -46,6 +147,14 @@ pub enum Currency {
     Ngn,
     #[serde(rename = "GHS")]
     Ghs,
+    #[serde(rename = "ETB")]
+    Etb,
+    #[serde(rename = "MWK")]
+    Mwk,
+    #[serde(rename = "XOF")]
+    Xof,
+    #[serde(rename = "XAF")]
+    Xaf,
 }
 
 impl Currency {
@@ -59,10 +168,58 @@ impl Currency {
             Currency::Zmw => "ZMW",
             Currency::Ngn => "NGN",
             Currency::Ghs => "GHS",
+            Currency::Etb => "ETB",
+            Currency::Mwk => "MWK",
+            Currency::Xof => "XOF",
+            Currency::Xaf => "XAF",
         }
     }
 }
 
+impl FromStr for Currency {
+    type Err = AfricasTalkingError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "KES" => Ok(Currency::Kes),
+            "USD" => Ok(Currency::Usd),
+            "UGX" => Ok(Currency::Ugx),
+            "TZS" => Ok(Currency::Tzs),
+            "RWF" => Ok(Currency::Rwf),
+            "ZMW" => Ok(Currency::Zmw),
+            "NGN" => Ok(Currency::Ngn),
+            "GHS" => Ok(Currency::Ghs),
+            "ETB" => Ok(Currency::Etb),
+            "MWK" => Ok(Currency::Mwk),
+            "XOF" => Ok(Currency::Xof),
+            "XAF" => Ok(Currency::Xaf),
+            _ => Err(AfricasTalkingError::validation(format!(
+                "Unsupported currency code: {s:?}"
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&str> for Currency {
+    type Error = AfricasTalkingError;
+
+    fn try_from(value: &str) -> Result<Self> {
+        value.parse()
+    }
+}
+
+/// Parse an amount returned by the API (e.g. `"KES 1234.50"`) into its
+/// currency and numeric value
+pub(crate) fn parse_amount(value: &str) -> Result<(Currency, f64)> {
+    let invalid = || AfricasTalkingError::validation(format!("Invalid amount: {value:?}"));
+
+    let (code, amount) = value.trim().split_once(' ').ok_or_else(invalid)?;
+    let currency = code.parse::<Currency>().map_err(|_| invalid())?;
+    let amount = amount.trim().parse::<f64>().map_err(|_| invalid())?;
+
+    Ok((currency, amount))
+}
+
 /// Phone number with country code
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PhoneNumber {
@@ -77,11 +234,258 @@ impl PhoneNumber {
             country_code: None,
         }
     }
-    
+
     pub fn with_country_code<S: Into<String>>(number: S, country_code: S) -> Self {
         Self {
             number: number.into(),
             country_code: Some(country_code.into()),
         }
     }
+
+    /// Parse and normalize a phone number
+    ///
+    /// Spaces, dashes, dots and brackets are stripped. International numbers
+    /// (leading `+` or `00`) may use any E.164 country code. Numbers in local
+    /// format (leading `0`) get the dialing code of `default_country`, an
+    /// ISO 3166 code such as `"KE"`.
+    ///
+    /// ```rust
+    /// use africastalking::PhoneNumber;
+    ///
+    /// let number = PhoneNumber::parse("0712 345-678", "KE").unwrap();
+    /// assert_eq!(number.as_e164(), "+254712345678");
+    /// assert!(PhoneNumber::parse("07l2345678", "KE").is_err());
+    /// ```
+    pub fn parse(input: &str, default_country: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            AfricasTalkingError::validation(format!("Invalid phone number {input:?}: {reason}"))
+        };
+
+        let cleaned: String = input
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+            .collect();
+
+        if cleaned.is_empty() {
+            return Err(invalid("empty"));
+        }
+
+        let default_code = || {
+            dialing_code(default_country)
+                .ok_or_else(|| invalid(&format!("unknown default country {default_country:?}")))
+        };
+        let (country_code, national) = if let Some(international) = cleaned
+            .strip_prefix('+')
+            .or_else(|| cleaned.strip_prefix("00"))
+        {
+            split_country_code(international).ok_or_else(|| invalid("missing country code"))?
+        } else if let Some(national) = cleaned.strip_prefix('0') {
+            (default_code()?, national)
+        } else {
+            let code = default_code()?;
+            match cleaned.strip_prefix(code) {
+                // Already carries the country code, just without the `+`
+                Some(national) if code.len() + national.len() >= 11 => (code, national),
+                _ => (code, cleaned.as_str()),
+            }
+        };
+
+        if national.is_empty() || !national.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid("must contain only digits"));
+        }
+
+        // E.164 numbers have at most 15 digits including the country code;
+        // no AT market has fewer than 8
+        let digits = country_code.len() + national.len();
+        if digits < 8 {
+            return Err(invalid("shorter than 8 digits"));
+        }
+        if digits > 15 {
+            return Err(invalid("longer than 15 digits"));
+        }
+
+        Ok(Self::with_country_code(
+            national.to_string(),
+            country_code.to_string(),
+        ))
+    }
+
+    /// The number in E.164 format, e.g. `+254712345678`
+    pub fn as_e164(&self) -> String {
+        match &self.country_code {
+            Some(code) => format!("+{}{}", code.trim_start_matches('+'), self.number),
+            None if self.number.starts_with('+') => self.number.clone(),
+            None => format!("+{}", self.number),
+        }
+    }
+}
+
+/// Normalize phone numbers to E.164 with [`PhoneNumber::parse`]
+pub(crate) fn normalize_phone_numbers<S: Into<String>>(
+    numbers: Vec<S>,
+    default_country: &str,
+) -> Result<Vec<String>> {
+    numbers
+        .into_iter()
+        .map(|number| Ok(PhoneNumber::parse(&number.into(), default_country)?.as_e164()))
+        .collect()
+}
+
+/// ISO 3166 country code to E.164 dialing code for the markets AT serves,
+/// used to expand numbers in local format
+const DIALING_CODES: &[(&str, &str)] = &[
+    ("KE", "254"),
+    ("UG", "256"),
+    ("TZ", "255"),
+    ("RW", "250"),
+    ("BI", "257"),
+    ("ET", "251"),
+    ("NG", "234"),
+    ("GH", "233"),
+    ("CI", "225"),
+    ("SN", "221"),
+    ("CM", "237"),
+    ("BJ", "229"),
+    ("ZM", "260"),
+    ("MW", "265"),
+    ("ZW", "263"),
+    ("MZ", "258"),
+    ("ZA", "27"),
+];
+
+/// Look up the dialing code for an ISO 3166 country code
+pub(crate) fn dialing_code(iso_code: &str) -> Option<&'static str> {
+    DIALING_CODES
+        .iter()
+        .find(|(iso, _)| iso.eq_ignore_ascii_case(iso_code))
+        .map(|(_, code)| *code)
+}
+
+/// Two-digit E.164 country codes; all others are `1`, `7`, or three digits
+/// (ITU-T E.164 assigned country codes)
+const TWO_DIGIT_COUNTRY_CODES: &[&str] = &[
+    "20", "27", "30", "31", "32", "33", "34", "36", "39", "40", "41", "43", "44", "45", "46", "47",
+    "48", "49", "51", "52", "53", "54", "55", "56", "57", "58", "60", "61", "62", "63", "64", "65",
+    "66", "81", "82", "84", "86", "90", "91", "92", "93", "94", "95", "98",
+];
+
+/// Split the digits after `+` into the country code and the rest
+fn split_country_code(number: &str) -> Option<(&str, &str)> {
+    let len = match number.as_bytes().first()? {
+        b'1' | b'7' => 1,
+        b'2'..=b'9'
+            if number
+                .get(..2)
+                .is_some_and(|code| TWO_DIGIT_COUNTRY_CODES.contains(&code)) =>
+        {
+            2
+        }
+        b'2'..=b'9' => 3,
+        _ => return None,
+    };
+    number.split_at_checked(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_expands_local_numbers_with_the_default_country() {
+        let number = PhoneNumber::parse("0712345678", "KE").unwrap();
+        assert_eq!(number.country_code.as_deref(), Some("254"));
+        assert_eq!(number.as_e164(), "+254712345678");
+
+        let number = PhoneNumber::parse("(0772) 123 456", "UG").unwrap();
+        assert_eq!(number.as_e164(), "+256772123456");
+    }
+
+    #[test]
+    fn parse_accepts_numbers_missing_only_the_plus() {
+        let number = PhoneNumber::parse("254712345678", "KE").unwrap();
+        assert_eq!(number.as_e164(), "+254712345678");
+    }
+
+    #[test]
+    fn parse_accepts_any_international_country_code() {
+        let cases = [
+            ("+254712345678", "254", "+254712345678"),
+            ("+14155550100", "1", "+14155550100"),
+            ("+447700900123", "44", "+447700900123"),
+            ("00447700900123", "44", "+447700900123"),
+            ("+79123456789", "7", "+79123456789"),
+            ("+3548401234", "354", "+3548401234"),
+        ];
+        for (input, code, e164) in cases {
+            let number = PhoneNumber::parse(input, "KE").unwrap();
+            assert_eq!(number.country_code.as_deref(), Some(code), "{input}");
+            assert_eq!(number.as_e164(), e164);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_invalid_input() {
+        for input in [
+            "",
+            "   ",
+            "+",
+            "+0712345678",
+            "07l2345678",
+            "+2547123456789012",
+            "0712-345-67x",
+        ] {
+            let error = PhoneNumber::parse(input, "KE").unwrap_err();
+            assert!(
+                matches!(error, AfricasTalkingError::Validation(_)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_rejects_numbers_shorter_than_8_digits() {
+        for input in ["+11", "0712", "1", "+2547123"] {
+            let error = PhoneNumber::parse(input, "KE").unwrap_err();
+            assert!(
+                matches!(error, AfricasTalkingError::Validation(_)),
+                "{input}"
+            );
+        }
+        assert!(PhoneNumber::parse("+25471234", "KE").is_ok());
+    }
+
+    #[test]
+    fn parse_rejects_local_numbers_for_unknown_countries() {
+        assert!(PhoneNumber::parse("0712345678", "XX").is_err());
+        assert!(PhoneNumber::parse("+447700900123", "XX").is_ok());
+    }
+
+    #[test]
+    fn currency_round_trips_every_variant() {
+        let currencies = [
+            Currency::Kes,
+            Currency::Usd,
+            Currency::Ugx,
+            Currency::Tzs,
+            Currency::Rwf,
+            Currency::Zmw,
+            Currency::Ngn,
+            Currency::Ghs,
+            Currency::Etb,
+            Currency::Mwk,
+            Currency::Xof,
+            Currency::Xaf,
+        ];
+        for currency in currencies {
+            let code = currency.as_str();
+            assert_eq!(code.parse::<Currency>().unwrap(), currency);
+            assert_eq!(Currency::try_from(code).unwrap(), currency);
+            assert_eq!(code.to_lowercase().parse::<Currency>().unwrap(), currency);
+
+            let json = serde_json::to_string(&currency).unwrap();
+            assert_eq!(json, format!("\"{code}\""));
+            assert_eq!(serde_json::from_str::<Currency>(&json).unwrap(), currency);
+        }
+        assert!("EUR".parse::<Currency>().is_err());
+    }
 }
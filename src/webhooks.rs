@@ -0,0 +1,143 @@
+//! Shared-secret signatures for callbacks
+//!
+//! AfricasTalking does not sign the callbacks it sends. This is an app-level
+//! scheme for deployments where a gateway or relay in front of the app
+//! forwards callbacks and signs them with a secret shared with the app.
+//!
+//! The signature is the HMAC-SHA256 (RFC 2104) of the raw request body keyed
+//! with the shared secret, hex encoded and optionally prefixed with
+//! `sha256=`, sent in the [`SIGNATURE_HEADER`] header.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Header carrying the callback signature, set by the signing relay
+pub const SIGNATURE_HEADER: &str = "x-callback-signature";
+
+/// Check a callback signature against the raw request body
+///
+/// The comparison is constant time.
+///
+/// ```rust
+/// use africastalking::webhooks::{sign, verify_signature};
+///
+/// let body = b"sessionId=ATUid_1&text=1";
+/// let signature = sign("secret", body);
+///
+/// assert!(verify_signature("secret", body, &signature));
+/// assert!(!verify_signature("other-secret", body, &signature));
+/// ```
+pub fn verify_signature(secret: &str, raw_body: &[u8], provided_signature: &str) -> bool {
+    let provided = provided_signature.trim();
+    let provided = provided.strip_prefix("sha256=").unwrap_or(provided);
+
+    let Ok(expected) = hex::decode(provided) else {
+        return false;
+    };
+
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(raw_body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Compute the hex encoded signature for a request body
+pub fn sign(secret: &str, raw_body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(raw_body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(feature = "axum")]
+pub use extract::{SignedForm, WebhookSecret};
+
+#[cfg(feature = "axum")]
+mod extract {
+    use super::{SIGNATURE_HEADER, verify_signature};
+    use axum::{
+        body::Bytes,
+        extract::{FromRef, FromRequest, Request},
+        http::StatusCode,
+    };
+    use serde::de::DeserializeOwned;
+
+    /// Shared secret used to verify callbacks, provided through axum state
+    #[derive(Debug, Clone)]
+    pub struct WebhookSecret(pub String);
+
+    /// Form extractor that rejects callbacks with a missing or invalid signature
+    #[derive(Debug, Clone)]
+    pub struct SignedForm<T>(pub T);
+
+    impl<T, S> FromRequest<S> for SignedForm<T>
+    where
+        T: DeserializeOwned,
+        S: Send + Sync,
+        WebhookSecret: FromRef<S>,
+    {
+        type Rejection = (StatusCode, &'static str);
+
+        async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+            let secret = WebhookSecret::from_ref(state);
+            let signature = req
+                .headers()
+                .get(SIGNATURE_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+                .ok_or((StatusCode::UNAUTHORIZED, "Missing callback signature"))?;
+
+            let body = Bytes::from_request(req, state)
+                .await
+                .map_err(|_| (StatusCode::BAD_REQUEST, "Failed to read request body"))?;
+
+            if !verify_signature(&secret.0, &body, &signature) {
+                return Err((StatusCode::UNAUTHORIZED, "Invalid callback signature"));
+            }
+
+            serde_urlencoded::from_bytes(&body)
+                .map(SignedForm)
+                .map_err(|_| (StatusCode::UNPROCESSABLE_ENTITY, "Invalid form body"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4231 test case 2
+    const SECRET: &str = "Jefe";
+    const BODY: &[u8] = b"what do ya want for nothing?";
+    const SIGNATURE: &str = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+
+    #[test]
+    fn sign_matches_the_rfc_4231_vector() {
+        assert_eq!(sign(SECRET, BODY), SIGNATURE);
+    }
+
+    #[test]
+    fn verify_accepts_the_rfc_4231_vector() {
+        assert!(verify_signature(SECRET, BODY, SIGNATURE));
+        assert!(verify_signature(
+            SECRET,
+            BODY,
+            &format!("sha256={SIGNATURE}")
+        ));
+        assert!(verify_signature(SECRET, BODY, &SIGNATURE.to_uppercase()));
+    }
+
+    #[test]
+    fn verify_rejects_mismatches() {
+        assert!(!verify_signature("jefe", BODY, SIGNATURE));
+        assert!(!verify_signature(
+            SECRET,
+            b"what do ya want for nothing",
+            SIGNATURE
+        ));
+        assert!(!verify_signature(SECRET, BODY, &SIGNATURE[..62]));
+        assert!(!verify_signature(SECRET, BODY, "not hex"));
+        assert!(!verify_signature(SECRET, BODY, ""));
+    }
+}